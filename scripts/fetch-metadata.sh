@@ -0,0 +1,26 @@
+#!/usr/bin/env bash
+# Fetch the runtime metadata artifacts used by the generated runtime types.
+#
+# Requires `subxt-cli`: cargo install subxt-cli
+set -euo pipefail
+
+ARTIFACTS="$(dirname "$0")/../artifacts"
+
+fetch() {
+    local url="$1"
+    local file="$2"
+    echo "Fetching metadata from ${url} into ${file}"
+    subxt metadata --url "${url}" -f bytes > "${ARTIFACTS}/${file}"
+}
+
+fetch wss://rpc-kusama.helixstreet.io kusama.scale
+fetch wss://asset-hub-kusama.dotters.network asset_hub_kusama.scale
+
+fetch wss://dot-rpc.stakeworld.io polkadot.scale
+fetch wss://polkadot-asset-hub-rpc.polkadot.io asset_hub_polkadot.scale
+
+fetch wss://rpc.ibp.network/paseo paseo.scale
+fetch wss://sys.ibp.network/asset-hub-paseo asset_hub_paseo.scale
+
+fetch wss://westend-rpc.polkadot.io westend.scale
+fetch wss://westend-asset-hub-rpc.polkadot.io asset_hub_westend.scale
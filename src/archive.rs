@@ -0,0 +1,688 @@
+use std::path::Path;
+
+use codec::Encode;
+use subxt::{OnlineClient, PolkadotConfig};
+
+use crate::async_backing::AsyncBackingStats;
+use crate::config::NetworkConfig;
+use crate::coretime::{self, CoreTracker, GapCause, format_cores};
+use crate::duplicate::{self, BlockInfo};
+use crate::equivocation::{EquivocationDetector, EvidenceBlock};
+use crate::inherents::Inherents;
+use crate::runtime::RELAY_CHAIN_SLOT_DURATION_MS;
+use crate::timeline::{RelayInclusions, TimelineStats};
+use crate::timestamp::{self, RelayParentTime};
+use crate::upgrade::{Segment, SpecTracker, format_spec};
+
+/// Archive mode walking the parachain blocks.
+///
+/// The range covers the `blocks_diff` blocks before the latest one, which is excluded.
+pub async fn archive(
+    network: &NetworkConfig,
+    blocks_diff: u32,
+    evidence_dir: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let now = std::time::Instant::now();
+    let relay_chain_url = network.relay_chain_url.as_str();
+    let parachain_url = network.parachain_url.as_str();
+    let chain_name = network.display_name.as_str();
+
+    let api = OnlineClient::<PolkadotConfig>::from_url(parachain_url).await?;
+    println!("Connection with parachain established.");
+
+    let rpc_client = subxt_rpcs::RpcClient::from_url(parachain_url).await?;
+    let legacy_methods: subxt_rpcs::LegacyRpcMethods<PolkadotConfig> =
+        subxt_rpcs::LegacyRpcMethods::new(rpc_client.clone());
+    let chain_head_client =
+        subxt_rpcs::ChainHeadRpcMethods::<PolkadotConfig>::new(rpc_client.clone());
+    println!("Connection with RPC client established.");
+
+    let relay_rpc_client = subxt_rpcs::RpcClient::from_url(relay_chain_url).await?;
+    let relay_chain_head_client =
+        subxt_rpcs::ChainHeadRpcMethods::<PolkadotConfig>::new(relay_rpc_client.clone());
+    let relay_legacy_methods: subxt_rpcs::LegacyRpcMethods<PolkadotConfig> =
+        subxt_rpcs::LegacyRpcMethods::new(relay_rpc_client.clone());
+    let relay_api =
+        OnlineClient::<PolkadotConfig>::from_rpc_client(relay_rpc_client.clone()).await?;
+    println!("Connection with relay chain established.");
+
+    let latest = api.blocks().at_latest().await?;
+    let number = latest.header().number;
+    println!(
+        "{chain_name}: Latest parachain block #{number}, hash={:?}",
+        latest.hash()
+    );
+
+    let relay_head = relay_api.blocks().at_latest().await?.header().number;
+    let mut inclusions =
+        RelayInclusions::new(&relay_api, &relay_legacy_methods, network, relay_head);
+    let mut timelines = TimelineStats::default();
+
+    let mut target = number.saturating_sub(blocks_diff);
+    let mut timestamps = std::collections::HashMap::new();
+    let mut duplicated_blocks = std::collections::HashMap::new();
+    let mut last_author = None;
+
+    let mut authoring_statistincs = std::collections::HashMap::new();
+    let mut authoring_in_row = std::collections::HashMap::new();
+    let mut num_produced = 1;
+
+    let mut prev_timestamp = None;
+    let mut prev_timestamp_ms = None;
+    let mut prev_parent = None;
+    let mut timestamp_anomalies = std::collections::BTreeMap::new();
+
+    let mut block_infos = std::collections::HashMap::new();
+    let mut duplicate_causes = std::collections::BTreeMap::new();
+    let mut blocks_with_inherent_issues = 0;
+    let mut equivocations = EquivocationDetector::new(network.window);
+    let mut equivocations_total = 0;
+
+    let mut async_backing =
+        AsyncBackingStats::new(network.thresholds.async_backing_underuse_blocks);
+    let mut first_configuration = None;
+    let mut last_relay_parent_hash = None;
+
+    let mut cores = CoreTracker::default();
+    let mut gap_causes = std::collections::BTreeMap::new();
+    // Relay parents advancing by more relay blocks than a slow block are gaps in block production.
+    let expected_jump = (network.slow_block_ms() / RELAY_CHAIN_SLOT_DURATION_MS).max(1) as i64;
+
+    // Statistics are split at every runtime upgrade of the parachain.
+    let mut spec = SpecTracker::new(network.runtime.bundled_spec_versions().1);
+    let mut segments: Vec<Segment> = Vec::new();
+
+    let mut delta_values = Vec::with_capacity(blocks_diff as usize);
+    let mut num_jumps = Vec::with_capacity(blocks_diff as usize);
+
+    while target != number {
+        let hash = legacy_methods
+            .chain_get_block_hash(Some(target.into()))
+            .await?
+            .ok_or_else(|| format!("Parachain block #{target} not found"))?;
+        let block = api.blocks().at(hash).await?;
+        let block_number = block.header().number;
+
+        target += 1;
+
+        let Some(author) = network.author.extract(&block.header().digest) else {
+            println!(
+                "  No {} pre-runtime digest in block #{block_number}, skipped.\n",
+                network.author.engine()
+            );
+            continue;
+        };
+
+        // Blocks of the same author competing at this height are equivocations.
+        let mut block_equivocations: Vec<_> = equivocations
+            .record(
+                block_number,
+                &author,
+                EvidenceBlock {
+                    hash,
+                    header: block.header().encode(),
+                },
+            )
+            .into_iter()
+            .collect();
+        let siblings = chain_head_client
+            .archive_v1_hash_by_height(block_number as usize)
+            .await?;
+        // Without a fork at this height, the canonical block is the only one.
+        if siblings.len() > 1 {
+            for sibling in siblings.into_iter().filter(|sibling| *sibling != hash) {
+                timelines.record_dropped(block_number, sibling);
+                let Some(header) = legacy_methods.chain_get_header(Some(sibling)).await? else {
+                    continue;
+                };
+                let Some(sibling_author) = network.author.extract(&header.digest) else {
+                    continue;
+                };
+                block_equivocations.extend(equivocations.record(
+                    block_number,
+                    &sibling_author,
+                    EvidenceBlock {
+                        hash: sibling,
+                        header: header.encode(),
+                    },
+                ));
+            }
+        }
+        equivocations_total += block_equivocations.len();
+
+        let extrinsics = block
+            .extrinsics()
+            .await
+            .inspect_err(|err| println!("Failed to decode extrinsics: {:?}", err))?;
+
+        let inherents = Inherents::locate(&network.runtime, &extrinsics);
+        if !inherents.issues.is_empty() {
+            blocks_with_inherent_issues += 1;
+        }
+
+        let relay_chain_parent = inherents.relay_parent_number;
+        let timestamp = inherents.timestamp_bytes;
+        let timestamp_ms = inherents.timestamp;
+        let mut timestamp_human = Default::default();
+
+        let spec_version = network.runtime.parachain_spec_version(&block).await?;
+        let spec_change = spec.update(spec_version);
+        if spec_change.is_some() || segments.is_empty() {
+            segments.push(Segment::new(spec_version, block_number));
+        }
+        let mut upgrade_events = Vec::new();
+        for evt in block.events().await?.iter() {
+            if let Some(upgrade) = network.runtime.parachain_upgrade_event(&evt?) {
+                upgrade_events.push(upgrade);
+            }
+        }
+
+        let mut duplicate = None;
+
+        if let Some(timestamp_ms) = timestamp_ms {
+            timestamp_human = timestamp::to_datetime(timestamp_ms);
+
+            match timestamps.entry(timestamp_ms) {
+                std::collections::hash_map::Entry::Occupied(mut entry) => {
+                    let block = entry.get_mut();
+                    duplicated_blocks.insert(*block, block_number);
+                    duplicate = Some((*block, block_number));
+                }
+                std::collections::hash_map::Entry::Vacant(entry) => {
+                    entry.insert(block_number);
+                }
+            }
+        }
+
+        let relay_parent_hash = match relay_chain_parent {
+            Some(parent) => {
+                relay_legacy_methods
+                    .chain_get_block_hash(Some(parent.into()))
+                    .await?
+            }
+            None => None,
+        };
+        let relay_parent_time = match relay_parent_hash {
+            Some(hash) => {
+                let header = relay_legacy_methods.chain_get_header(Some(hash)).await?;
+                RelayParentTime {
+                    timestamp_ms: network.runtime.relay_timestamp(&relay_api, hash).await?,
+                    slot_ms: header.and_then(|header| timestamp::babe_slot_ms(&header.digest)),
+                }
+            }
+            None => RelayParentTime::default(),
+        };
+
+        // The bounds of the analysed range are those in force at its first relay parent.
+        if first_configuration.is_none()
+            && let Some(hash) = relay_parent_hash
+        {
+            first_configuration = network.runtime.host_configuration(&relay_api, hash).await?;
+            if let Some(configuration) = first_configuration {
+                println!("Host configuration at relay parent {hash:?}: {configuration}\n");
+                async_backing.set_configuration(configuration);
+            }
+        }
+        last_relay_parent_hash = relay_parent_hash.or(last_relay_parent_hash);
+
+        let core_change = match relay_parent_hash {
+            Some(hash) => {
+                let para_cores = network
+                    .runtime
+                    .para_cores(&relay_api, hash, network.para_id)
+                    .await?;
+                cores.update(para_cores)
+            }
+            None => None,
+        };
+
+        // Sample the relay chain blocks of a gap to tell a missing core from an unused one.
+        let mut gap = None;
+        if let (Some(prev_parent), Some(parent)) = (prev_parent, relay_chain_parent)
+            && parent as i64 - prev_parent as i64 > expected_jump
+        {
+            let samples = coretime::gap_samples(prev_parent, parent);
+            let mut without_core = 0;
+            for &number in &samples {
+                let Some(hash) = relay_legacy_methods
+                    .chain_get_block_hash(Some(number.into()))
+                    .await?
+                else {
+                    continue;
+                };
+                let para_cores = network
+                    .runtime
+                    .para_cores(&relay_api, hash, network.para_id)
+                    .await?;
+                if para_cores.is_empty() {
+                    without_core += 1;
+                }
+            }
+
+            let cause = if without_core > 0 {
+                GapCause::NoCore {
+                    blocks: without_core,
+                    sampled: samples.len() as u32,
+                }
+            } else {
+                GapCause::Unused
+            };
+            *gap_causes.entry(cause.to_string()).or_insert(0) += 1;
+            gap = Some((parent - prev_parent, cause));
+        }
+
+        // Follow the candidate of the block from its relay parent to its inclusion.
+        let timeline = match relay_chain_parent {
+            Some(parent) => {
+                inclusions.prune(parent);
+                let timeline = inclusions
+                    .timeline(
+                        block.hash(),
+                        parent,
+                        first_configuration
+                            .map(|configuration| configuration.max_relay_parent_age()),
+                    )
+                    .await?;
+                timelines.record(block_number, block.hash(), &timeline);
+                Some(timeline)
+            }
+            None => None,
+        };
+
+        let unincluded_segment = network.runtime.unincluded_segment_len(&block).await?;
+        let async_backing_issues =
+            async_backing.record_unincluded_segment(block_number, unincluded_segment);
+
+        let drift =
+            timestamp_ms.and_then(|timestamp_ms| relay_parent_time.render_drift(timestamp_ms));
+        let limits = network.timestamp_limits(
+            first_configuration.map(|configuration| configuration.allowed_ancestry_len),
+        );
+        let anomalies = timestamp_ms
+            .map(|timestamp_ms| {
+                timestamp::check(timestamp_ms, prev_timestamp_ms, relay_parent_time, limits)
+            })
+            .unwrap_or_default();
+        for anomaly in &anomalies {
+            timestamp_anomalies
+                .entry(anomaly.name())
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+        }
+
+        if let Some(segment) = segments.last_mut() {
+            let block_time_ms =
+                timestamp_ms
+                    .zip(prev_timestamp_ms)
+                    .map(|(timestamp_ms, prev_timestamp_ms)| {
+                        timestamp_ms.saturating_sub(prev_timestamp_ms)
+                    });
+            segment.record_block(block_number, block_time_ms);
+            segment.duplicated_timestamps += duplicate.is_some() as u32;
+            segment.timestamp_anomalies += anomalies.len() as u32;
+            segment.inherent_issues += !inherents.issues.is_empty() as u32;
+        }
+
+        let author_bytes = author.id.clone();
+        let block_info = BlockInfo {
+            relay_parent: relay_chain_parent,
+            author: author.clone(),
+        };
+        authoring_statistincs
+            .entry(author_bytes.clone())
+            .and_modify(|count| *count += 1)
+            .or_insert(1);
+
+        let same_author = last_author
+            .as_ref()
+            .map(|last| last == &author_bytes)
+            .unwrap_or(false);
+        if same_author {
+            num_produced += 1;
+        } else {
+            if num_produced > 1 {
+                authoring_in_row
+                    .entry(num_produced)
+                    .and_modify(|count| *count += 1)
+                    .or_insert(1);
+            }
+
+            num_produced = 1;
+        }
+
+        let ident = (0..num_produced - 1).map(|_| "    ").collect::<String>();
+
+        let author_label = if same_author {
+            format!("Same (times: {})", num_produced)
+        } else {
+            "New".into()
+        };
+        last_author = Some(author_bytes);
+
+        if let Some((origin_block, _duplicate_number)) = duplicate {
+            println!(
+                "{ident}[X] {chain_name}: Block #{block_number}, hash={:?}, spec={}",
+                block.hash(),
+                format_spec(spec_version),
+            );
+            println!("{ident}  |--> {author_label} Author: {author}");
+            println!(
+                "{ident}  |--> ({}) Duplicate Timestamp extrinsic found: initial={} current_block={} Timestamp.Set: 0x{} | {:?}\n",
+                duplicated_blocks.len(),
+                origin_block,
+                block_number,
+                hex::encode(timestamp.unwrap_or_default()),
+                timestamp_human,
+            );
+            println!("{ident}  |--> Relay Chain Parent: {:?}", relay_chain_parent);
+            if let Some(drift) = &drift {
+                println!("{ident}  |--> Relay Chain Parent {drift}");
+            }
+            for anomaly in &anomalies {
+                println!("{ident}  |--> [!] {anomaly}");
+            }
+            for issue in &inherents.issues {
+                println!("{ident}  |--> [!] Inherents: {issue}");
+            }
+            println!("{ident}  |--> Unincluded segment: {unincluded_segment} blocks");
+            for issue in &async_backing_issues {
+                println!("{ident}  |--> [!] Async backing: {issue}");
+            }
+            if let Some(change) = &core_change {
+                println!("{ident}  |--> [!] Coretime: {change}");
+            }
+            if let Some((jump, cause)) = gap {
+                println!("{ident}  |--> [!] Gap of {jump} relay blocks: {cause}");
+            }
+            match &timeline {
+                Some(timeline) if timeline.not_found() => {
+                    println!("{ident}  |--> [!] Not found: {timeline}")
+                }
+                Some(timeline) => println!("{ident}  |--> Inclusion: {timeline}"),
+                None => {}
+            }
+            if let Some(change) = spec_change {
+                println!("{ident}  |--> [!!!] Runtime upgrade: {change}");
+            }
+            for upgrade in &upgrade_events {
+                println!("{ident}  |--> [!] Runtime upgrade: {upgrade}");
+            }
+            for equivocation in &block_equivocations {
+                println!("{ident}  |--> [!!!] Equivocation: {equivocation}");
+                match equivocation.export(network, evidence_dir) {
+                    Ok(path) => println!("{ident}  |--> Evidence written to {}", path.display()),
+                    Err(err) => println!("{ident}  |--> [!] Failed to write the evidence: {err}"),
+                }
+            }
+
+            // Check if the parachain contained a fork during that time.
+            let blocks = chain_head_client
+                .archive_v1_hash_by_height(origin_block as usize)
+                .await
+                .map_err(|err| {
+                    eprintln!("Failed to fetch archive hash for block {origin_block}: {err}");
+                    err
+                })?;
+            println!(
+                "{ident}  |--> Archive hash for block {origin_block}: {:?}",
+                blocks
+            );
+
+            if let Some(origin_info) = block_infos.get(&origin_block) {
+                let causes =
+                    duplicate::classify(&network.author, origin_info, &block_info, blocks.len());
+                duplicate_causes
+                    .entry(causes[0])
+                    .and_modify(|count| *count += 1)
+                    .or_insert(1);
+
+                let causes = causes
+                    .iter()
+                    .map(|cause| cause.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                println!("{ident}  |--> Root cause: {causes}");
+            }
+
+            let blocks = chain_head_client
+                .archive_v1_hash_by_height(origin_block as usize - 1)
+                .await
+                .map_err(|err| {
+                    eprintln!(
+                        "Failed to fetch archive hash for block {}: {err}",
+                        origin_block - 1
+                    );
+                    err
+                })?;
+            println!(
+                "{ident}  |--> Archive hash for block {}: {:?}",
+                origin_block - 1,
+                blocks
+            );
+
+            if let Some(parent) = relay_chain_parent {
+                let relay_chain_block = relay_chain_head_client
+                    .archive_v1_hash_by_height(parent as usize)
+                    .await
+                    .map_err(|err| {
+                        eprintln!(
+                            "Failed to fetch relay chain archive hash for block {parent}: {err}"
+                        );
+                        err
+                    })?;
+                println!(
+                    "{ident}  |--> Relay Chain Archive hash for block {parent}: {:?}\n",
+                    relay_chain_block
+                );
+            }
+
+            if let (Some(prev_timestamp), Some(prev_parent), Some(parent)) =
+                (prev_timestamp, prev_parent, relay_chain_parent)
+            {
+                let jumped = parent as i64 - prev_parent as i64;
+                delta_values.push(
+                    timestamp_human
+                        .signed_duration_since(prev_timestamp)
+                        .num_seconds(),
+                );
+                num_jumps.push(jumped);
+
+                println!(
+                    "{ident}  |--> Elapsed {:?} seconds | jumped num={:?} relay chain blocks",
+                    timestamp_human
+                        .signed_duration_since(prev_timestamp)
+                        .num_seconds(),
+                    jumped,
+                );
+            }
+        } else {
+            println!(
+                "{ident}{chain_name}: Block #{block_number}, hash={:?}, spec={}",
+                block.hash(),
+                format_spec(spec_version),
+            );
+            println!("{ident}  |--> {author_label} Author: {author}");
+            println!(
+                "{ident}  |--> Timestamp.Set: 0x{} | {:?}",
+                hex::encode(timestamp.unwrap_or_default()),
+                timestamp_human,
+            );
+
+            match relay_chain_parent {
+                Some(parent) => println!("{ident}  |--> Relay Chain Parent: {:?}", parent),
+                None => println!("{ident}  |--> Relay Chain Parent: missing"),
+            }
+            if let Some(drift) = &drift {
+                println!("{ident}  |--> Relay Chain Parent {drift}");
+            }
+            for anomaly in &anomalies {
+                println!("{ident}  |--> [!] {anomaly}");
+            }
+            for issue in &inherents.issues {
+                println!("{ident}  |--> [!] Inherents: {issue}");
+            }
+            println!("{ident}  |--> Unincluded segment: {unincluded_segment} blocks");
+            for issue in &async_backing_issues {
+                println!("{ident}  |--> [!] Async backing: {issue}");
+            }
+            if let Some(change) = &core_change {
+                println!("{ident}  |--> [!] Coretime: {change}");
+            }
+            if let Some((jump, cause)) = gap {
+                println!("{ident}  |--> [!] Gap of {jump} relay blocks: {cause}");
+            }
+            match &timeline {
+                Some(timeline) if timeline.not_found() => {
+                    println!("{ident}  |--> [!] Not found: {timeline}")
+                }
+                Some(timeline) => println!("{ident}  |--> Inclusion: {timeline}"),
+                None => {}
+            }
+            if let Some(change) = spec_change {
+                println!("{ident}  |--> [!!!] Runtime upgrade: {change}");
+            }
+            for upgrade in &upgrade_events {
+                println!("{ident}  |--> [!] Runtime upgrade: {upgrade}");
+            }
+            for equivocation in &block_equivocations {
+                println!("{ident}  |--> [!!!] Equivocation: {equivocation}");
+                match equivocation.export(network, evidence_dir) {
+                    Ok(path) => println!("{ident}  |--> Evidence written to {}", path.display()),
+                    Err(err) => println!("{ident}  |--> [!] Failed to write the evidence: {err}"),
+                }
+            }
+
+            if let Some(parent) = relay_chain_parent {
+                let relay_chain_block = relay_chain_head_client
+                    .archive_v1_hash_by_height(parent as usize)
+                    .await
+                    .map_err(|err| {
+                        eprintln!(
+                            "Failed to fetch relay chain archive hash for block {parent}: {err}"
+                        );
+                        err
+                    })?;
+                println!(
+                    "{ident}  |--> Relay Chain Archive hash for block {parent}: {:?}",
+                    relay_chain_block
+                );
+            }
+
+            if let (Some(prev_timestamp), Some(prev_parent), Some(parent)) =
+                (prev_timestamp, prev_parent, relay_chain_parent)
+            {
+                let jumped = parent as i64 - prev_parent as i64;
+                delta_values.push(
+                    timestamp_human
+                        .signed_duration_since(prev_timestamp)
+                        .num_seconds(),
+                );
+                num_jumps.push(jumped);
+
+                println!(
+                    "{ident}  |--> Elapsed {:?} seconds | jumped num={:?} relay chain blocks",
+                    timestamp_human
+                        .signed_duration_since(prev_timestamp)
+                        .num_seconds(),
+                    jumped,
+                );
+            }
+
+            println!();
+        }
+
+        if timestamp_ms.is_some() {
+            prev_timestamp = Some(timestamp_human);
+            prev_timestamp_ms = timestamp_ms;
+        }
+        if relay_chain_parent.is_some() {
+            prev_parent = relay_chain_parent;
+        }
+        block_infos.insert(block_number, block_info);
+    }
+
+    println!("Archive completed successfully.");
+    println!(
+        " Average block time: {:.2} seconds (expected {:.2} seconds)",
+        {
+            if !delta_values.is_empty() {
+                delta_values.iter().sum::<i64>() as f64 / delta_values.len() as f64
+            } else {
+                0.0
+            }
+        },
+        network.expected_block_time_ms as f64 / 1000.0
+    );
+    println!(
+        " Blocks slower than {}ms: {}",
+        network.slow_block_ms(),
+        delta_values
+            .iter()
+            .filter(|delta| **delta * 1000 > network.slow_block_ms() as i64)
+            .count()
+    );
+
+    let duplicated_percent = duplicated_blocks.len() as f64 / blocks_diff as f64 * 100.0;
+    println!(
+        "Number of sequential blocks with the same timestamp: {} / {} ({:.2}%)",
+        duplicated_blocks.len(),
+        blocks_diff,
+        duplicated_percent
+    );
+    if duplicated_percent > network.thresholds.duplicated_timestamps_percent {
+        println!(
+            " WARNING: duplicated timestamps exceed the threshold of {:.2}%",
+            network.thresholds.duplicated_timestamps_percent
+        );
+    }
+    println!(" - produced in a row: {:#?}", authoring_in_row);
+    println!(
+        " - blocks with missing, duplicated or out of order inherents: {}",
+        blocks_with_inherent_issues
+    );
+    println!(" - timestamp anomalies: {:#?}", timestamp_anomalies);
+    println!(" - collator equivocations: {equivocations_total}");
+    println!(" - duplicated timestamps by primary root cause:");
+    for (cause, count) in &duplicate_causes {
+        println!("     {cause}: {count}");
+    }
+    println!(" - production gaps by cause:");
+    for (cause, count) in &gap_causes {
+        println!("     {cause}: {count}");
+    }
+    if let Some(url) = &network.coretime_url {
+        let coretime_api = OnlineClient::<PolkadotConfig>::from_url(url.as_str()).await?;
+        let broker_cores = coretime::broker_cores(&coretime_api, network.para_id).await?;
+        println!(
+            " - current Broker workload: cores {}",
+            format_cores(&broker_cores)
+        );
+    }
+    println!(" - runtime segments:");
+    for segment in &segments {
+        println!("{}", segment.render());
+    }
+    if let Some(warning) = spec.bundled_mismatch() {
+        println!(" [!] Runtime: {warning}");
+    }
+    for line in timelines.render() {
+        println!("{line}");
+    }
+    for line in async_backing.render() {
+        println!("{line}");
+    }
+    if let Some(hash) = last_relay_parent_hash {
+        let last_configuration = network.runtime.host_configuration(&relay_api, hash).await?;
+        if last_configuration != first_configuration
+            && let Some(configuration) = last_configuration
+        {
+            println!(
+                " [!] Host configuration changed within the range, at the last relay parent: {configuration}"
+            );
+        }
+    }
+
+    println!("Took {:?}", now.elapsed());
+
+    Ok(())
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use subxt::error::BlockError;
+use subxt::utils::H256;
+use subxt::{OnlineClient, PolkadotConfig};
+
+use crate::config::NetworkConfig;
+use crate::runtime::InclusionEvent;
+use crate::summary::percent;
+
+/// Archive mode walking the relay chain instead of the parachain.
+///
+/// Every relay chain block of the range is scanned for the `ParaInclusion` events of the
+/// parachain, and the `para_head` of each candidate is mapped to its parachain block.
+///
+/// Like [`crate::archive::archive`], the range covers the `blocks_diff` blocks before the
+/// latest one, which is excluded.
+pub async fn archive_relay(
+    network: &NetworkConfig,
+    blocks_diff: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let now = std::time::Instant::now();
+    let chain_name = network.display_name.as_str();
+
+    let api = OnlineClient::<PolkadotConfig>::from_url(network.parachain_url.as_str()).await?;
+    println!("Connection with parachain established.");
+
+    let relay_rpc_client =
+        subxt_rpcs::RpcClient::from_url(network.relay_chain_url.as_str()).await?;
+    let relay_legacy_methods: subxt_rpcs::LegacyRpcMethods<PolkadotConfig> =
+        subxt_rpcs::LegacyRpcMethods::new(relay_rpc_client.clone());
+    let relay_api =
+        OnlineClient::<PolkadotConfig>::from_rpc_client(relay_rpc_client.clone()).await?;
+    println!("Connection with relay chain established.");
+
+    let latest = relay_api.blocks().at_latest().await?;
+    let number = latest.header().number;
+    println!(
+        "Latest relay chain block #{number}, hash={:?}\n",
+        latest.hash()
+    );
+
+    // Parachain block numbers by `para_head`, as candidates are reported when backed and included.
+    let mut para_blocks: HashMap<H256, Option<u32>> = HashMap::new();
+
+    let mut relay_blocks = 0u64;
+    let mut backed_total = 0u64;
+    let mut included_total = 0u64;
+    let mut timed_out_total = 0u64;
+    let mut unknown_heads = 0u64;
+
+    // Runs of consecutive relay chain blocks without a candidate of the parachain backed.
+    let mut idle_runs = Vec::new();
+    let mut idle_since = None;
+    let mut idle_blocks = 0u64;
+
+    // The latest block is excluded, as in `archive`.
+    for block_number in number.saturating_sub(blocks_diff)..number {
+        let Some(hash) = relay_legacy_methods
+            .chain_get_block_hash(Some(block_number.into()))
+            .await?
+        else {
+            println!("Relay Block #{block_number}: hash not found, skipped.\n");
+            continue;
+        };
+        let block = relay_api.blocks().at(hash).await?;
+        relay_blocks += 1;
+
+        let mut rows = Vec::new();
+        let (mut backed, mut included, mut timed_out) = (0, 0, 0);
+        for evt in block.events().await?.iter() {
+            let Some(event) = network.runtime.inclusion_event(&evt?) else {
+                continue;
+            };
+            if event.para_id() != network.para_id {
+                continue;
+            }
+
+            let para_head = event.para_head();
+            let para_block = match para_blocks.get(&para_head) {
+                Some(para_block) => *para_block,
+                None => {
+                    let para_block = match api.blocks().at(para_head).await {
+                        Ok(block) => Some(block.header().number),
+                        // Only a head the parachain node does not know is unknown.
+                        Err(subxt::Error::Block(BlockError::NotFound(_))) => None,
+                        Err(err) => return Err(err.into()),
+                    };
+                    para_blocks.insert(para_head, para_block);
+                    para_block
+                }
+            };
+            if para_block.is_none() {
+                unknown_heads += 1;
+            }
+            let para_block = para_block
+                .map(|number| format!("#{number}"))
+                .unwrap_or_else(|| "unknown".into());
+
+            let row = match event {
+                InclusionEvent::CandidateBacked {
+                    core_index,
+                    group_index,
+                    ..
+                } => {
+                    backed += 1;
+                    format!(
+                        "CandidateBacked: {chain_name} block {para_block} para_head={para_head:?} core={core_index} group={group_index}"
+                    )
+                }
+                InclusionEvent::CandidateIncluded {
+                    core_index,
+                    group_index,
+                    ..
+                } => {
+                    included += 1;
+                    format!(
+                        "CandidateIncluded: {chain_name} block {para_block} para_head={para_head:?} core={core_index} group={group_index}"
+                    )
+                }
+                InclusionEvent::CandidateTimedOut { core_index, .. } => {
+                    timed_out += 1;
+                    format!(
+                        "[!] CandidateTimedOut: {chain_name} block {para_block} para_head={para_head:?} core={core_index}"
+                    )
+                }
+            };
+            rows.push(row);
+        }
+        backed_total += backed;
+        included_total += included;
+        timed_out_total += timed_out;
+
+        println!(
+            "Relay Block #{block_number}, hash={hash:?}: backed {backed} | included {included} | timed out {timed_out}"
+        );
+        for row in &rows {
+            println!(" |--> {row}");
+        }
+
+        if backed == 0 {
+            idle_blocks += 1;
+            idle_since.get_or_insert(block_number);
+            println!(" |--> [!] Nothing backed for {chain_name}");
+        } else if let Some(since) = idle_since.take() {
+            idle_runs.push((since, block_number - 1));
+        }
+        println!();
+    }
+    if let Some(since) = idle_since {
+        idle_runs.push((since, number - 1));
+    }
+
+    println!("Relay archive completed successfully.");
+    println!(" Relay blocks: {relay_blocks}");
+    println!(
+        " Candidates: backed {backed_total} | included {included_total} | timed out {timed_out_total} | para_head not found on the parachain {unknown_heads}"
+    );
+    println!(
+        " Relay blocks without a candidate backed: {idle_blocks} ({:.2}%)",
+        percent(idle_blocks, relay_blocks)
+    );
+    if let Some((from, to)) = idle_runs.iter().max_by_key(|(from, to)| to - from) {
+        println!(
+            " Longest run without a candidate backed: #{from}..=#{to} ({} relay blocks)",
+            to - from + 1
+        );
+    }
+    println!(" - runs of several relay blocks without a candidate backed:");
+    for (from, to) in idle_runs.iter().filter(|(from, to)| to > from) {
+        println!("     #{from}..=#{to} ({} relay blocks)", to - from + 1);
+    }
+
+    println!("Took {:?}", now.elapsed());
+
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+
+use crate::runtime::HostConfiguration;
+use crate::window::Recent;
+
+/// Observed async backing behaviour of the parachain outside of the relay chain bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsyncBackingIssue {
+    /// A candidate was backed more than `allowed_ancestry_len + 1` relay blocks after its relay parent.
+    RelayParentTooOld { age: u32, max: u32 },
+    /// The unincluded segment grew beyond `max_candidate_depth + 1` blocks.
+    UnincludedSegmentTooLong { length: u32, max: u32 },
+    /// Parachain blocks `from..=to` were built with at most one unincluded block,
+    /// although the relay chain allows a deeper pipeline.
+    Underused { from: u32, to: u32 },
+}
+
+impl fmt::Display for AsyncBackingIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AsyncBackingIssue::RelayParentTooOld { age, max } => write!(
+                f,
+                "relay parent is {age} relay blocks old, above the allowed {max}"
+            ),
+            AsyncBackingIssue::UnincludedSegmentTooLong { length, max } => write!(
+                f,
+                "unincluded segment of {length} blocks, above max_candidate_depth + 1 = {max}"
+            ),
+            AsyncBackingIssue::Underused { from, to } => write!(
+                f,
+                "blocks #{from}..#{to} ({} blocks) built without async backing pipelining",
+                to - from + 1
+            ),
+        }
+    }
+}
+
+/// Average and maximum of the observed values.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Samples {
+    pub count: u64,
+    total: u64,
+    pub max: u32,
+}
+
+impl Samples {
+    pub fn record(&mut self, value: u32) {
+        self.count += 1;
+        self.total += value as u64;
+        self.max = self.max.max(value);
+    }
+
+    pub fn average(&self) -> f64 {
+        self.total as f64 / self.count.max(1) as f64
+    }
+}
+
+/// Relay parent ages and unincluded segment lengths, checked against the host configuration.
+#[derive(Debug)]
+pub struct AsyncBackingStats {
+    configuration: Option<HostConfiguration>,
+    /// Runs of blocks without pipelining shorter than this are not reported.
+    underuse_blocks: u32,
+    relay_parent_age: Samples,
+    unincluded_segment: Samples,
+    relay_parent_violations: u64,
+    unincluded_violations: u64,
+    /// The ongoing run of blocks without pipelining.
+    underused: Option<(u32, u32)>,
+    /// The last runs of blocks without pipelining.
+    underused_periods: Recent<(u32, u32)>,
+}
+
+impl AsyncBackingStats {
+    pub fn new(underuse_blocks: u32) -> Self {
+        Self {
+            configuration: None,
+            underuse_blocks,
+            relay_parent_age: Samples::default(),
+            unincluded_segment: Samples::default(),
+            relay_parent_violations: 0,
+            unincluded_violations: 0,
+            underused: None,
+            underused_periods: Recent::default(),
+        }
+    }
+
+    pub fn set_configuration(&mut self, configuration: HostConfiguration) {
+        self.configuration = Some(configuration);
+    }
+
+    /// Record the relay chain blocks between the relay parent of a candidate and its backing.
+    pub fn record_relay_parent_age(&mut self, age: u32) -> Option<AsyncBackingIssue> {
+        self.relay_parent_age.record(age);
+
+        let max = self.configuration?.max_relay_parent_age();
+        if age <= max {
+            return None;
+        }
+
+        self.relay_parent_violations += 1;
+        Some(AsyncBackingIssue::RelayParentTooOld { age, max })
+    }
+
+    /// Record the unincluded segment length after parachain block `number`.
+    ///
+    /// Runs of blocks without pipelining are reported once they end.
+    pub fn record_unincluded_segment(
+        &mut self,
+        number: u32,
+        length: u32,
+    ) -> Vec<AsyncBackingIssue> {
+        self.unincluded_segment.record(length);
+
+        let Some(configuration) = self.configuration else {
+            return Vec::new();
+        };
+        let mut issues = Vec::new();
+
+        let max = configuration.max_unincluded_segment();
+        if length > max {
+            self.unincluded_violations += 1;
+            issues.push(AsyncBackingIssue::UnincludedSegmentTooLong { length, max });
+        }
+
+        if configuration.max_candidate_depth > 0 && length <= 1 {
+            let (from, _) = self.underused.unwrap_or((number, number));
+            self.underused = Some((from, number));
+        } else if let Some((from, to)) = self.underused.take()
+            && to - from + 1 >= self.underuse_blocks
+        {
+            self.underused_periods.push((from, to));
+            issues.push(AsyncBackingIssue::Underused { from, to });
+        }
+
+        issues
+    }
+
+    /// Render the configuration and the observed values, followed by one line per underused period.
+    pub fn render(&self) -> Vec<String> {
+        let configuration = self
+            .configuration
+            .map(|configuration| configuration.to_string())
+            .unwrap_or_else(|| "unknown".into());
+        let mut lines = vec![format!(" Async backing: {configuration}")];
+
+        // Relay parent ages are only known when the relay chain events are followed.
+        if self.relay_parent_age.count > 0 {
+            lines.push(format!(
+                "   relay parent age: avg {:.2} | max {} relay blocks | violations {}",
+                self.relay_parent_age.average(),
+                self.relay_parent_age.max,
+                self.relay_parent_violations
+            ));
+        }
+        lines.push(format!(
+            "   unincluded segment: avg {:.2} | max {} blocks | violations {}",
+            self.unincluded_segment.average(),
+            self.unincluded_segment.max,
+            self.unincluded_violations
+        ));
+
+        let ongoing = self
+            .underused
+            .filter(|(from, to)| to - from + 1 >= self.underuse_blocks);
+        if self.underused_periods.forgotten() > 0 {
+            lines.push(format!(
+                "   ... {} earlier runs without pipelining",
+                self.underused_periods.forgotten()
+            ));
+        }
+        for (from, to) in self.underused_periods.iter().chain(ongoing.as_ref()) {
+            lines.push(format!(
+                "   [!] {}",
+                AsyncBackingIssue::Underused {
+                    from: *from,
+                    to: *to
+                }
+            ));
+        }
+
+        lines
+    }
+}
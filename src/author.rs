@@ -0,0 +1,163 @@
+use serde::Deserialize;
+use subxt::config::substrate::{Digest, DigestItem};
+
+const AURA_ENGINE_ID: [u8; 4] = *b"aura";
+const NIMBUS_ENGINE_ID: [u8; 4] = *b"nmbs";
+
+/// How the author of a parachain block is identified from its digest.
+///
+/// ```toml
+/// author = "aura"
+/// author = "nimbus"
+/// author = { pre-runtime = "BABE" }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuthorExtractor {
+    /// The slot of the Aura pre-runtime digest.
+    #[default]
+    Aura,
+    /// The author ID of the Nimbus pre-runtime digest.
+    Nimbus,
+    /// The payload of the pre-runtime digest of the given consensus engine.
+    PreRuntime(ConsensusEngine),
+}
+
+/// A four character consensus engine ID, such as `aura` or `nmbs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ConsensusEngine([u8; 4]);
+
+impl TryFrom<String> for ConsensusEngine {
+    type Error = String;
+
+    fn try_from(engine: String) -> Result<Self, Self::Error> {
+        let id = engine.as_bytes().try_into().map_err(|_| {
+            format!("Consensus engine ID must be exactly 4 bytes long, got {engine:?}")
+        })?;
+
+        Ok(Self(id))
+    }
+}
+
+impl std::fmt::Display for ConsensusEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&String::from_utf8_lossy(&self.0))
+    }
+}
+
+/// The author of a parachain block, as identified by an [`AuthorExtractor`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockAuthor {
+    /// The pre-runtime digest payload, compared between blocks to detect the same author.
+    pub id: Vec<u8>,
+    /// The slot of the block, for engines whose digest carries one.
+    pub slot: Option<u64>,
+}
+
+impl std::fmt::Display for BlockAuthor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.slot {
+            Some(slot) => write!(f, "{:?} (slot {slot})", hex::encode(&self.id)),
+            None => write!(f, "{:?}", hex::encode(&self.id)),
+        }
+    }
+}
+
+impl AuthorExtractor {
+    /// The consensus engine of the pre-runtime digest identifying the author.
+    pub fn engine(&self) -> ConsensusEngine {
+        match self {
+            AuthorExtractor::Aura => ConsensusEngine(AURA_ENGINE_ID),
+            AuthorExtractor::Nimbus => ConsensusEngine(NIMBUS_ENGINE_ID),
+            AuthorExtractor::PreRuntime(engine) => *engine,
+        }
+    }
+
+    /// Find the author in the digest, regardless of the position of the pre-runtime digest.
+    pub fn extract(&self, digest: &Digest) -> Option<BlockAuthor> {
+        let engine = self.engine();
+        let payload = digest.logs.iter().find_map(|log| match log {
+            DigestItem::PreRuntime(id, payload) if *id == engine.0 => Some(payload),
+            _ => None,
+        })?;
+
+        let slot = match self {
+            AuthorExtractor::Aura => Some(u64::from_le_bytes(payload.as_slice().try_into().ok()?)),
+            _ => None,
+        };
+
+        Some(BlockAuthor {
+            id: payload.clone(),
+            slot,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(logs: Vec<DigestItem>) -> Digest {
+        Digest { logs }
+    }
+
+    #[test]
+    fn aura_slot_anywhere_in_the_digest() {
+        let digest = digest(vec![
+            DigestItem::PreRuntime(NIMBUS_ENGINE_ID, vec![1; 32]),
+            DigestItem::PreRuntime(AURA_ENGINE_ID, 42u64.to_le_bytes().to_vec()),
+        ]);
+
+        assert_eq!(
+            AuthorExtractor::Aura.extract(&digest),
+            Some(BlockAuthor {
+                id: 42u64.to_le_bytes().to_vec(),
+                slot: Some(42),
+            })
+        );
+    }
+
+    #[test]
+    fn malformed_aura_slot() {
+        let digest = digest(vec![DigestItem::PreRuntime(AURA_ENGINE_ID, vec![1, 2, 3])]);
+
+        assert_eq!(AuthorExtractor::Aura.extract(&digest), None);
+    }
+
+    #[test]
+    fn nimbus_and_custom_engines_have_no_slot() {
+        let digest = digest(vec![
+            DigestItem::PreRuntime(AURA_ENGINE_ID, 42u64.to_le_bytes().to_vec()),
+            DigestItem::PreRuntime(NIMBUS_ENGINE_ID, vec![7; 32]),
+            DigestItem::PreRuntime(*b"BABE", vec![9; 16]),
+        ]);
+
+        assert_eq!(
+            AuthorExtractor::Nimbus.extract(&digest),
+            Some(BlockAuthor {
+                id: vec![7; 32],
+                slot: None,
+            })
+        );
+        let engine = ConsensusEngine::try_from("BABE".to_string()).unwrap();
+        assert_eq!(
+            AuthorExtractor::PreRuntime(engine).extract(&digest),
+            Some(BlockAuthor {
+                id: vec![9; 16],
+                slot: None,
+            })
+        );
+    }
+
+    #[test]
+    fn missing_pre_runtime_digest() {
+        assert_eq!(AuthorExtractor::Nimbus.extract(&digest(Vec::new())), None);
+    }
+
+    #[test]
+    fn engine_ids_must_be_four_bytes() {
+        assert!(ConsensusEngine::try_from("aura".to_string()).is_ok());
+        assert!(ConsensusEngine::try_from("nimbus".to_string()).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+
+use crate::runtime::{SessionGroups, Validator};
+
+/// Sessions whose groups and validators are kept, older sessions are dropped.
+const RECENT_SESSIONS: u32 = 6;
+
+/// Number of groups and validators listed in the summary.
+const TOP_ENTRIES: usize = 10;
+
+/// Statistics of a backing group within a session.
+#[derive(Debug, Default, Clone)]
+pub struct GroupStats {
+    pub backed: u64,
+    pub included: u64,
+    pub timed_out: u64,
+    /// Relay chain blocks between backing and inclusion, for candidates seen being backed.
+    inclusion_wait_samples: u64,
+    inclusion_wait_total: u64,
+    inclusion_wait_max: u32,
+}
+
+impl GroupStats {
+    /// Average number of relay chain blocks between backing and inclusion.
+    pub fn average_inclusion_wait(&self) -> f64 {
+        if self.inclusion_wait_samples == 0 {
+            return 0.0;
+        }
+
+        self.inclusion_wait_total as f64 / self.inclusion_wait_samples as f64
+    }
+}
+
+/// Statistics of a validator, over all the groups it was part of in the recent sessions.
+#[derive(Debug, Default, Clone)]
+pub struct ValidatorStats {
+    /// The last session the validator was seen in.
+    session: u32,
+    /// Candidates of the parachain the validator voted for.
+    pub votes: u64,
+    /// Candidates backed by the group of the validator, without its vote.
+    pub missed: u64,
+    /// Candidates backed by the group of the validator which timed out.
+    pub timed_out: u64,
+}
+
+/// Per backing group and per validator statistics of the candidates of the parachain,
+/// over the last [`RECENT_SESSIONS`] sessions.
+#[derive(Debug, Default)]
+pub struct BackingStats {
+    /// Keyed by session and group index.
+    groups: BTreeMap<(u32, u32), GroupStats>,
+    validators: BTreeMap<Validator, ValidatorStats>,
+    /// The newest session recorded.
+    session: u32,
+}
+
+impl BackingStats {
+    /// The stats of a group, `None` if its session was already dropped.
+    ///
+    /// A newer session drops the groups and validators of the sessions out of the window.
+    fn group(&mut self, session: u32, group: u32) -> Option<&mut GroupStats> {
+        if session > self.session {
+            self.session = session;
+            let oldest = session.saturating_sub(RECENT_SESSIONS - 1);
+            self.groups = self.groups.split_off(&(oldest, 0));
+            self.validators.retain(|_, stats| stats.session >= oldest);
+        }
+        if session + RECENT_SESSIONS <= self.session {
+            return None;
+        }
+
+        Some(self.groups.entry((session, group)).or_default())
+    }
+
+    /// The stats of a validator seen in `session`.
+    fn validator(&mut self, session: u32, validator: Validator) -> &mut ValidatorStats {
+        let stats = self.validators.entry(validator).or_default();
+        stats.session = stats.session.max(session);
+        stats
+    }
+
+    /// Record a candidate backed by `group`.
+    ///
+    /// `votes` are the members of the group which voted, in group order, when known.
+    pub fn record_backed(&mut self, groups: &SessionGroups, group: u32, votes: Option<&[bool]>) {
+        let Some(stats) = self.group(groups.session, group) else {
+            return;
+        };
+        stats.backed += 1;
+
+        for (position, validator) in groups.members(group).iter().enumerate() {
+            let stats = self.validator(groups.session, *validator);
+            match votes.map(|votes| votes.get(position).copied().unwrap_or(false)) {
+                Some(true) => stats.votes += 1,
+                Some(false) => stats.missed += 1,
+                None => (),
+            }
+        }
+    }
+
+    /// Record a candidate of `group` included after `wait` relay chain blocks.
+    pub fn record_included(&mut self, session: u32, group: u32, wait: Option<u32>) {
+        let Some(stats) = self.group(session, group) else {
+            return;
+        };
+        stats.included += 1;
+
+        if let Some(wait) = wait {
+            stats.inclusion_wait_samples += 1;
+            stats.inclusion_wait_total += wait as u64;
+            stats.inclusion_wait_max = stats.inclusion_wait_max.max(wait);
+        }
+    }
+
+    /// Record a candidate backed by `group` in `session` which timed out.
+    ///
+    /// `members` are the validators of the group, when the session is still known.
+    pub fn record_timed_out(&mut self, session: u32, group: u32, members: &[Validator]) {
+        let Some(stats) = self.group(session, group) else {
+            return;
+        };
+        stats.timed_out += 1;
+
+        for validator in members {
+            self.validator(session, *validator).timed_out += 1;
+        }
+    }
+
+    /// Render the slowest groups and the validators that missed the most votes or had the most
+    /// candidates timing out, worst first.
+    pub fn render(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        let mut groups = self.groups.iter().collect::<Vec<_>>();
+        groups.sort_by(|(_, a), (_, b)| {
+            (b.timed_out, b.average_inclusion_wait())
+                .partial_cmp(&(a.timed_out, a.average_inclusion_wait()))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        for ((session, group), stats) in groups.iter().take(TOP_ENTRIES) {
+            lines.push(format!(
+                "   session {session} group {group}: backed {} | included {} | timed out {} | inclusion wait avg {:.2} | max {} relay blocks",
+                stats.backed,
+                stats.included,
+                stats.timed_out,
+                stats.average_inclusion_wait(),
+                stats.inclusion_wait_max
+            ));
+        }
+        if groups.len() > TOP_ENTRIES {
+            lines.push(format!("   ... {} more groups", groups.len() - TOP_ENTRIES));
+        }
+
+        let mut validators = self
+            .validators
+            .iter()
+            .filter(|(_, stats)| stats.missed > 0 || stats.timed_out > 0)
+            .collect::<Vec<_>>();
+        validators.sort_by_key(|(_, stats)| std::cmp::Reverse((stats.timed_out, stats.missed)));
+        for (validator, stats) in validators.iter().take(TOP_ENTRIES) {
+            lines.push(format!(
+                "   validator #{} 0x{}: votes {} | missed {} | timed out {}",
+                validator.index,
+                hex::encode(validator.key),
+                stats.votes,
+                stats.missed,
+                stats.timed_out
+            ));
+        }
+
+        if validators.len() > TOP_ENTRIES {
+            lines.push(format!(
+                "   ... {} more validators",
+                validators.len() - TOP_ENTRIES
+            ));
+        }
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validator(index: u32) -> Validator {
+        Validator {
+            key: [index as u8; 32],
+            index,
+        }
+    }
+
+    fn session(session: u32) -> SessionGroups {
+        SessionGroups {
+            session,
+            groups: vec![vec![validator(0), validator(1)], vec![validator(2)]],
+        }
+    }
+
+    #[test]
+    fn old_sessions_are_dropped() {
+        let mut stats = BackingStats::default();
+        stats.record_backed(&session(1), 0, Some(&[true, false]));
+        stats.record_backed(&session(1 + RECENT_SESSIONS), 1, Some(&[true]));
+
+        assert_eq!(stats.groups.keys().collect::<Vec<_>>(), [&(7, 1)]);
+        assert_eq!(stats.validators.keys().collect::<Vec<_>>(), [&validator(2)]);
+
+        // Late events of a dropped session are ignored.
+        stats.record_included(1, 0, Some(1));
+        stats.record_timed_out(1, 0, &[validator(0)]);
+        assert_eq!(stats.groups.len(), 1);
+        assert_eq!(stats.validators.len(), 1);
+    }
+
+    #[test]
+    fn sessions_within_the_window_are_kept() {
+        let mut stats = BackingStats::default();
+        stats.record_backed(&session(2), 0, None);
+        stats.record_backed(&session(1 + RECENT_SESSIONS), 0, None);
+        stats.record_included(2, 0, Some(3));
+
+        assert_eq!(stats.groups[&(2, 0)].included, 1);
+        assert_eq!(stats.groups[&(2, 0)].average_inclusion_wait(), 3.0);
+    }
+
+    #[test]
+    fn render_lists_the_slowest_groups_first() {
+        let mut stats = BackingStats::default();
+        for group in 0..TOP_ENTRIES as u32 + 2 {
+            stats.record_backed(&session(1), group, None);
+            stats.record_included(1, group, Some(group));
+        }
+        stats.record_timed_out(1, 3, &[validator(0)]);
+
+        let lines = stats.render();
+        assert!(lines[0].starts_with("   session 1 group 3:"));
+        assert!(lines[1].starts_with("   session 1 group 11:"));
+        assert_eq!(lines[TOP_ENTRIES], "   ... 2 more groups");
+        assert!(lines[TOP_ENTRIES + 1].starts_with("   validator #0 "));
+        assert_eq!(lines.len(), TOP_ENTRIES + 2);
+    }
+}
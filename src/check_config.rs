@@ -0,0 +1,164 @@
+use subxt::{OnlineClient, PolkadotConfig};
+
+use crate::config::NetworkConfig;
+use crate::coretime::format_cores;
+use crate::runtime::RELAY_CHAIN_SLOT_DURATION_MS;
+
+/// Report whether the parachain and relay chain runtimes are configured for async backing.
+///
+/// Only the latest state of both chains is inspected, no blocks are scanned.
+pub async fn check_config(network: &NetworkConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let chain_name = network.display_name.as_str();
+
+    let api = OnlineClient::<PolkadotConfig>::from_url(network.parachain_url.as_str()).await?;
+    println!("Connection with parachain established.");
+    let relay_api =
+        OnlineClient::<PolkadotConfig>::from_url(network.relay_chain_url.as_str()).await?;
+    println!("Connection with relay chain established.\n");
+
+    let parachain_block = api.blocks().at_latest().await?;
+    let relay_block = relay_api.blocks().at_latest().await?;
+    println!(
+        "{chain_name}: async backing readiness at block #{}, hash={:?} (relay chain block #{}, hash={:?})",
+        parachain_block.header().number,
+        parachain_block.hash(),
+        relay_block.header().number,
+        relay_block.hash()
+    );
+
+    let parachain = network.runtime.parachain_configuration(&api).await?;
+    let relay = network
+        .runtime
+        .host_configuration(&relay_api, relay_block.hash())
+        .await?
+        .ok_or("Configuration::ActiveConfig is not set on the relay chain")?;
+    let cores = network
+        .runtime
+        .para_cores(&relay_api, relay_block.hash(), network.para_id)
+        .await?;
+
+    let mut issues = 0;
+    let mut check = |ok: bool, message: String| {
+        if ok {
+            println!("  [ok] {message}");
+        } else {
+            issues += 1;
+            println!("  [!] {message}");
+        }
+    };
+
+    // With elastic scaling the parachain authors one block per core in each relay chain slot.
+    let mut distinct_cores = cores.clone();
+    distinct_cores.sort_unstable();
+    distinct_cores.dedup();
+    let blocks_per_relay_slot =
+        RELAY_CHAIN_SLOT_DURATION_MS as f64 / parachain.slot_duration_ms.max(1) as f64;
+    check(
+        slot_duration_matches(
+            parachain.slot_duration_ms,
+            distinct_cores.len() as u64,
+            network.expected_block_time_ms,
+        ),
+        format!(
+            "Aura::SlotDuration: {}ms ({blocks_per_relay_slot:.2} blocks per relay chain slot of {RELAY_CHAIN_SLOT_DURATION_MS}ms, {} cores assigned, expected block time {}ms)",
+            parachain.slot_duration_ms,
+            distinct_cores.len(),
+            network.expected_block_time_ms
+        ),
+    );
+    check(
+        parachain.minimum_period_ms == 0,
+        format!(
+            "Timestamp::MinimumPeriod: {}ms (async backing expects 0, slots are enforced by Aura)",
+            parachain.minimum_period_ms
+        ),
+    );
+
+    // The velocity and capacity of the consensus hook are compiled into the runtime
+    // and not stored on chain, hence only its current usage is shown.
+    println!(
+        "  [?] ConsensusHook: not checked, {} blocks authored in the current slot (expected {blocks_per_relay_slot:.2})",
+        parachain
+            .authored_in_slot
+            .map(|authored| authored.to_string())
+            .unwrap_or_else(|| "unknown".into())
+    );
+    check(
+        parachain.unincluded_segment_len <= relay.max_unincluded_segment(),
+        format!(
+            "ParachainSystem::UnincludedSegment: {} blocks (relay chain allows max_candidate_depth + 1 = {})",
+            parachain.unincluded_segment_len,
+            relay.max_unincluded_segment()
+        ),
+    );
+    check(
+        relay.max_candidate_depth > 0 && relay.allowed_ancestry_len > 0,
+        format!("Relay chain Configuration::ActiveConfig: {relay}"),
+    );
+
+    match parachain.async_backing_params {
+        Some((max_candidate_depth, allowed_ancestry_len)) => check(
+            max_candidate_depth == relay.max_candidate_depth
+                && allowed_ancestry_len == relay.allowed_ancestry_len,
+            format!(
+                "ParachainSystem::HostConfiguration: max_candidate_depth={max_candidate_depth} allowed_ancestry_len={allowed_ancestry_len} (relay chain max_candidate_depth={} allowed_ancestry_len={})",
+                relay.max_candidate_depth, relay.allowed_ancestry_len
+            ),
+        ),
+        None => check(
+            false,
+            "ParachainSystem::HostConfiguration: not set".to_string(),
+        ),
+    }
+
+    check(
+        !cores.is_empty(),
+        format!(
+            "ParaScheduler::ClaimQueue: para {} assigned to cores {} over the next {} relay chain blocks",
+            network.para_id,
+            format_cores(&cores),
+            relay.scheduling_lookahead
+        ),
+    );
+
+    println!();
+    if issues == 0 {
+        println!(
+            "{chain_name}: no async backing readiness issues found, the ConsensusHook is not checked."
+        );
+    } else {
+        println!("{chain_name}: {issues} async backing readiness issue(s) found.");
+    }
+
+    Ok(())
+}
+
+/// Returns true if the parachain authors a block per core in each relay chain slot,
+/// or at its expected block time.
+///
+/// A slower slot duration leaves the assigned cores unused.
+fn slot_duration_matches(slot_duration_ms: u64, cores: u64, expected_block_time_ms: u64) -> bool {
+    slot_duration_ms > 0
+        && (slot_duration_ms * cores.max(1) == RELAY_CHAIN_SLOT_DURATION_MS
+            || slot_duration_ms == expected_block_time_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slot_duration_follows_the_cores() {
+        assert!(slot_duration_matches(6000, 1, 6000));
+        assert!(slot_duration_matches(6000, 0, 6000));
+        assert!(slot_duration_matches(2000, 3, 6000));
+        assert!(slot_duration_matches(2000, 1, 2000));
+    }
+
+    #[test]
+    fn slower_slot_duration_fails() {
+        assert!(!slot_duration_matches(12000, 1, 6000));
+        assert!(!slot_duration_matches(6000, 3, 2000));
+        assert!(!slot_duration_matches(0, 1, 0));
+    }
+}
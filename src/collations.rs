@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use subxt::utils::H256;
+
+use crate::window::{FirstSeenWindow, WindowConfig};
+
+/// A mismatch between the parachain blocks imported by our node and the candidates backed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollationIssue {
+    /// A candidate was backed for a parachain block our RPC node never imported,
+    /// the collator did not propagate it to us.
+    NotImported { para_head: H256, backed_at: u32 },
+    /// A parachain block was imported but never backed, the collation was wasted.
+    NotBacked {
+        number: u32,
+        hash: H256,
+        imported_at: u32,
+    },
+}
+
+impl fmt::Display for CollationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CollationIssue::NotImported {
+                para_head,
+                backed_at,
+            } => write!(
+                f,
+                "candidate para_head={para_head:?} backed at relay #{backed_at} was never imported by the parachain RPC node"
+            ),
+            CollationIssue::NotBacked {
+                number,
+                hash,
+                imported_at,
+            } => write!(
+                f,
+                "block #{number}, hash={hash:?} imported at relay #{imported_at} was never backed, wasted collation"
+            ),
+        }
+    }
+}
+
+/// Matches the `para_head` of the backed candidates with the imported parachain blocks.
+pub struct CollationTracker {
+    timeout_blocks: u32,
+    /// Imported parachain blocks waiting for their candidate, with the relay chain block
+    /// they were imported at (unknown before the first relay chain block).
+    imported: HashMap<H256, (u32, Option<u32>)>,
+    /// Backed candidates whose parachain block was not imported yet.
+    unseen: HashMap<H256, u32>,
+    /// Every parachain block imported within the window, including those already matched
+    /// or expired from `imported`.
+    seen: FirstSeenWindow<H256>,
+}
+
+impl CollationTracker {
+    pub fn new(timeout_blocks: u32, window: WindowConfig) -> Self {
+        Self {
+            timeout_blocks,
+            imported: HashMap::new(),
+            unseen: HashMap::new(),
+            seen: FirstSeenWindow::new(window),
+        }
+    }
+
+    /// Record a parachain block imported while `relay_number` was the latest relay chain block.
+    ///
+    /// Returns the relay chain block its candidate was already backed in, for blocks
+    /// imported after their backing.
+    pub fn on_imported(
+        &mut self,
+        hash: H256,
+        number: u32,
+        relay_number: Option<u32>,
+    ) -> Option<u32> {
+        self.seen.insert(number, hash);
+        if let Some(backed_at) = self.unseen.remove(&hash) {
+            return Some(backed_at);
+        }
+
+        self.imported.insert(hash, (number, relay_number));
+        None
+    }
+
+    /// Record a candidate backed at relay chain block `relay_number`.
+    ///
+    /// Returns the number of its parachain block, if imported. Blocks already reported as
+    /// not backed, or backed again, are still known within the window.
+    pub fn on_backed(&mut self, para_head: H256, relay_number: u32) -> Option<u32> {
+        if let Some((number, _)) = self.imported.remove(&para_head) {
+            return Some(number);
+        }
+        if let Some(number) = self.seen.get(&para_head) {
+            return Some(number);
+        }
+
+        self.unseen.insert(para_head, relay_number);
+        None
+    }
+
+    /// Report the blocks and candidates still unmatched `timeout_blocks` after relay chain block `number`.
+    pub fn expire(&mut self, number: u32) -> Vec<CollationIssue> {
+        let timeout_blocks = self.timeout_blocks;
+        let mut issues = Vec::new();
+
+        self.unseen.retain(|para_head, backed_at| {
+            let expired = number.saturating_sub(*backed_at) >= timeout_blocks;
+            if expired {
+                issues.push(CollationIssue::NotImported {
+                    para_head: *para_head,
+                    backed_at: *backed_at,
+                });
+            }
+            !expired
+        });
+        self.imported.retain(|hash, (block_number, imported_at)| {
+            let imported_at = *imported_at.get_or_insert(number);
+            let expired = number.saturating_sub(imported_at) >= timeout_blocks;
+            if expired {
+                issues.push(CollationIssue::NotBacked {
+                    number: *block_number,
+                    hash: *hash,
+                    imported_at,
+                });
+            }
+            !expired
+        });
+
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> CollationTracker {
+        CollationTracker::new(
+            10,
+            WindowConfig {
+                blocks: Some(100),
+                seconds: None,
+            },
+        )
+    }
+
+    fn hash(byte: u8) -> H256 {
+        H256::repeat_byte(byte)
+    }
+
+    #[test]
+    fn imported_then_backed() {
+        let mut tracker = tracker();
+
+        assert_eq!(tracker.on_imported(hash(1), 5, Some(100)), None);
+        assert_eq!(tracker.on_backed(hash(1), 101), Some(5));
+        assert!(tracker.expire(200).is_empty());
+    }
+
+    #[test]
+    fn backed_then_imported() {
+        let mut tracker = tracker();
+
+        assert_eq!(tracker.on_backed(hash(1), 101), None);
+        assert_eq!(tracker.on_imported(hash(1), 5, Some(102)), Some(101));
+        assert!(tracker.expire(200).is_empty());
+    }
+
+    #[test]
+    fn unmatched_blocks_and_candidates_expire() {
+        let mut tracker = tracker();
+        tracker.on_imported(hash(1), 5, Some(100));
+        tracker.on_backed(hash(2), 100);
+
+        assert!(tracker.expire(109).is_empty());
+        assert_eq!(
+            tracker.expire(110),
+            vec![
+                CollationIssue::NotImported {
+                    para_head: hash(2),
+                    backed_at: 100
+                },
+                CollationIssue::NotBacked {
+                    number: 5,
+                    hash: hash(1),
+                    imported_at: 100
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn blocks_expired_before_their_backing_are_still_known() {
+        let mut tracker = tracker();
+        tracker.on_imported(hash(1), 5, Some(100));
+        assert_eq!(tracker.expire(110).len(), 1);
+
+        assert_eq!(tracker.on_backed(hash(1), 111), Some(5));
+        assert!(tracker.expire(200).is_empty());
+    }
+
+    #[test]
+    fn blocks_backed_twice_are_still_known() {
+        let mut tracker = tracker();
+        tracker.on_imported(hash(1), 5, Some(100));
+
+        assert_eq!(tracker.on_backed(hash(1), 101), Some(5));
+        assert_eq!(tracker.on_backed(hash(1), 105), Some(5));
+        assert!(tracker.expire(200).is_empty());
+    }
+}
@@ -0,0 +1,654 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::author::AuthorExtractor;
+use crate::health::HealthConfig;
+use crate::runtime::{RELAY_CHAIN_SLOT_DURATION_MS, Runtime};
+use crate::window::WindowConfig;
+
+/// The network used when neither `--chain` nor `default_network` is provided.
+const DEFAULT_NETWORK: &str = "kusama-asset-hub";
+
+/// Configuration file describing the networks the monitor can connect to.
+///
+/// ```toml
+/// default_network = "my-parachain"
+///
+/// [networks.my-parachain]
+/// display_name = "MyParachain"
+/// relay_chain_url = "wss://rpc-kusama.helixstreet.io"
+/// parachain_url = "wss://my-parachain.example.io"
+/// para_id = 2000
+/// runtime = "kusama"
+/// author = "aura"
+/// expected_block_time_ms = 6000
+/// relay_chain_fallback_urls = ["wss://kusama-rpc.polkadot.io"]
+/// parachain_fallback_urls = ["wss://my-parachain-2.example.io"]
+/// coretime_url = "wss://kusama-coretime-rpc.polkadot.io"
+/// parachain_observer_urls = ["wss://my-parachain-3.example.io"]
+///
+/// [networks.my-parachain.reconnect]
+/// initial_backoff_ms = 500
+/// max_backoff_ms = 30000
+/// max_backfill_blocks = 600
+///
+/// [networks.my-parachain.health]
+/// interval_secs = 60
+/// max_lag_blocks = 3
+/// min_peers = 1
+///
+/// [networks.my-parachain.window]
+/// blocks = 14400
+/// seconds = 86400
+///
+/// [networks.my-parachain.thresholds]
+/// slow_block_ms = 12000
+/// duplicated_timestamps_percent = 5.0
+/// timestamp_ahead_ms = 18000
+/// timestamp_lag_ms = 6000
+/// dispute_timeout_blocks = 600
+/// async_backing_underuse_blocks = 10
+/// on_demand_order_timeout_blocks = 20
+/// collation_timeout_blocks = 10
+/// inclusion_search_blocks = 10
+/// ```
+///
+/// Networks defined in the file are merged with the built-in presets,
+/// replacing any preset with the same name.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The network used when `--chain` is not provided.
+    #[serde(default)]
+    pub default_network: Option<String>,
+
+    /// Named networks.
+    #[serde(default)]
+    pub networks: BTreeMap<String, NetworkConfig>,
+}
+
+/// A named network: a relay chain and the parachain monitored on top of it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// Name printed in front of every parachain block.
+    pub display_name: String,
+
+    /// Relay chain RPC endpoint.
+    pub relay_chain_url: String,
+
+    /// Parachain RPC endpoint.
+    pub parachain_url: String,
+
+    /// Relay chain RPC endpoints used in order when the connection is lost.
+    #[serde(default)]
+    pub relay_chain_fallback_urls: Vec<String>,
+
+    /// Parachain RPC endpoints used in order when the connection is lost.
+    #[serde(default)]
+    pub parachain_fallback_urls: Vec<String>,
+
+    /// Coretime chain RPC endpoint, used to report the `Broker` workload of the parachain.
+    #[serde(default)]
+    pub coretime_url: Option<String>,
+
+    /// Additional parachain RPC endpoints, to compare how fast each node imports the blocks.
+    #[serde(default)]
+    pub parachain_observer_urls: Vec<String>,
+
+    /// The parachain ID on the relay chain.
+    pub para_id: u32,
+
+    /// The runtime metadata used to decode the relay chain and parachain blocks.
+    pub runtime: Runtime,
+
+    /// How the author of a parachain block is identified.
+    #[serde(default)]
+    pub author: AuthorExtractor,
+
+    /// The block time the parachain is expected to achieve.
+    #[serde(default = "default_block_time_ms")]
+    pub expected_block_time_ms: u64,
+
+    /// Thresholds used to flag unexpected behaviour.
+    #[serde(default)]
+    pub thresholds: Thresholds,
+
+    /// Backoff applied when reconnecting.
+    #[serde(default)]
+    pub reconnect: ReconnectConfig,
+
+    /// History kept by Subscribe for duplicate detection and statistics.
+    #[serde(default)]
+    pub window: WindowConfig,
+
+    /// Health checks of the RPC endpoints run by Subscribe.
+    #[serde(default)]
+    pub health: HealthConfig,
+}
+
+impl NetworkConfig {
+    /// Blocks produced later than this after their parent are flagged as slow.
+    ///
+    /// Defaults to twice the expected block time.
+    pub fn slow_block_ms(&self) -> u64 {
+        self.thresholds
+            .slow_block_ms
+            .unwrap_or(2 * self.expected_block_time_ms)
+    }
+
+    /// The drift allowed between a parachain timestamp and the clock of its relay parent.
+    ///
+    /// With async backing the relay parent can be `allowed_ancestry_len` relay blocks older
+    /// than the relay chain head, so the default bound follows it once the host
+    /// configuration is known, and twice the expected block time before.
+    pub fn timestamp_limits(&self, allowed_ancestry_len: Option<u32>) -> TimestampLimits {
+        let ahead_ms = allowed_ancestry_len
+            .map(|len| (len as u64 + 1) * RELAY_CHAIN_SLOT_DURATION_MS)
+            .unwrap_or(2 * self.expected_block_time_ms);
+
+        TimestampLimits {
+            ahead_ms: self.thresholds.timestamp_ahead_ms.unwrap_or(ahead_ms),
+            lag_ms: self
+                .thresholds
+                .timestamp_lag_ms
+                .unwrap_or(RELAY_CHAIN_SLOT_DURATION_MS),
+        }
+    }
+
+    /// The relay chain endpoints, starting with the primary one.
+    pub fn relay_chain_urls(&self) -> Vec<&str> {
+        std::iter::once(&self.relay_chain_url)
+            .chain(&self.relay_chain_fallback_urls)
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// The parachain endpoints, starting with the primary one.
+    pub fn parachain_urls(&self) -> Vec<&str> {
+        std::iter::once(&self.parachain_url)
+            .chain(&self.parachain_fallback_urls)
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Thresholds used to flag unexpected behaviour.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct Thresholds {
+    /// Blocks produced later than this after their parent are flagged as slow,
+    /// twice the expected block time when unset.
+    pub slow_block_ms: Option<u64>,
+
+    /// Warn when the percentage of blocks with duplicated timestamps exceeds this value.
+    pub duplicated_timestamps_percent: f64,
+
+    /// Parachain timestamps further ahead of their relay parent clock are flagged,
+    /// `allowed_ancestry_len + 1` relay chain slots when unset.
+    pub timestamp_ahead_ms: Option<u64>,
+
+    /// Parachain timestamps further behind their relay parent clock are flagged,
+    /// one relay chain slot when unset.
+    pub timestamp_lag_ms: Option<u64>,
+
+    /// Disputes not concluded within this many relay chain blocks are reported as timed out.
+    pub dispute_timeout_blocks: u32,
+
+    /// Runs of at least this many parachain blocks built without async backing pipelining are flagged.
+    pub async_backing_underuse_blocks: u32,
+
+    /// On-demand orders not followed by a backed candidate within this many relay chain blocks are wasted.
+    pub on_demand_order_timeout_blocks: u32,
+
+    /// Parachain blocks not backed within this many relay chain blocks of their import are wasted collations.
+    pub collation_timeout_blocks: u32,
+
+    /// Relay chain blocks after the backing of a candidate searched for its inclusion by `archive`.
+    pub inclusion_search_blocks: u32,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            slow_block_ms: None,
+            duplicated_timestamps_percent: 5.0,
+            timestamp_ahead_ms: None,
+            timestamp_lag_ms: None,
+            // One hour of 6s relay chain blocks.
+            dispute_timeout_blocks: 600,
+            async_backing_underuse_blocks: 10,
+            on_demand_order_timeout_blocks: 20,
+            collation_timeout_blocks: 10,
+            inclusion_search_blocks: 10,
+        }
+    }
+}
+
+/// The drift allowed between a parachain timestamp and its relay parent timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimestampLimits {
+    pub ahead_ms: u64,
+    pub lag_ms: u64,
+}
+
+/// Exponential backoff applied when reconnecting.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct ReconnectConfig {
+    /// Delay before the first reconnection attempt.
+    pub initial_backoff_ms: u64,
+
+    /// Upper bound of the delay between reconnection attempts.
+    pub max_backoff_ms: u64,
+
+    /// Maximum number of blocks fetched per chain to fill the gap left by an outage.
+    pub max_backfill_blocks: u32,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+            max_backfill_blocks: 600,
+        }
+    }
+}
+
+impl ReconnectConfig {
+    /// The delay before the given (zero based) reconnection attempt.
+    pub fn backoff(&self, attempt: u32) -> std::time::Duration {
+        let delay = self
+            .initial_backoff_ms
+            .saturating_mul(1 << attempt.min(16))
+            .min(self.max_backoff_ms);
+
+        std::time::Duration::from_millis(delay)
+    }
+}
+
+/// A built-in Asset Hub network.
+fn preset(
+    display_name: &str,
+    relay_chain_urls: &[&str],
+    parachain_urls: &[&str],
+    runtime: Runtime,
+) -> NetworkConfig {
+    let urls = |urls: &[&str]| urls.iter().map(|url| url.to_string()).collect();
+
+    NetworkConfig {
+        display_name: display_name.into(),
+        relay_chain_url: relay_chain_urls[0].into(),
+        parachain_url: parachain_urls[0].into(),
+        relay_chain_fallback_urls: urls(&relay_chain_urls[1..]),
+        parachain_fallback_urls: urls(&parachain_urls[1..]),
+        coretime_url: None,
+        parachain_observer_urls: Vec::new(),
+        para_id: 1000,
+        runtime,
+        author: AuthorExtractor::Aura,
+        expected_block_time_ms: default_block_time_ms(),
+        thresholds: Thresholds::default(),
+        reconnect: ReconnectConfig::default(),
+        window: WindowConfig::default(),
+        health: HealthConfig::default(),
+    }
+}
+
+fn default_block_time_ms() -> u64 {
+    6_000
+}
+
+/// Overrides provided on the command line for the selected network.
+#[derive(Debug, Default, Clone)]
+pub struct NetworkOverrides {
+    pub relay_chain_url: Option<String>,
+    pub parachain_url: Option<String>,
+    pub para_id: Option<u32>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let networks = [
+            (
+                "kusama-asset-hub",
+                preset(
+                    "AssetHubKusama",
+                    &[
+                        "wss://rpc-kusama.helixstreet.io",
+                        "wss://kusama-rpc.polkadot.io",
+                    ],
+                    &[
+                        "wss://asset-hub-kusama.dotters.network",
+                        "wss://kusama-asset-hub-rpc.polkadot.io",
+                    ],
+                    Runtime::Kusama,
+                ),
+            ),
+            (
+                "polkadot-asset-hub",
+                preset(
+                    "AssetHubPolkadot",
+                    &["wss://dot-rpc.stakeworld.io", "wss://rpc.polkadot.io"],
+                    &[
+                        "wss://polkadot-asset-hub-rpc.polkadot.io",
+                        "wss://asset-hub-polkadot.dotters.network",
+                    ],
+                    Runtime::Polkadot,
+                ),
+            ),
+            (
+                "paseo-asset-hub",
+                preset(
+                    "AssetHubPaseo",
+                    &["wss://rpc.ibp.network/paseo", "wss://paseo.dotters.network"],
+                    &[
+                        "wss://sys.ibp.network/asset-hub-paseo",
+                        "wss://asset-hub-paseo.dotters.network",
+                    ],
+                    Runtime::Paseo,
+                ),
+            ),
+            (
+                "westend-asset-hub",
+                preset(
+                    "AssetHubWestend",
+                    &["wss://westend-rpc.polkadot.io"],
+                    &["wss://westend-asset-hub-rpc.polkadot.io"],
+                    Runtime::Westend,
+                ),
+            ),
+        ];
+
+        Self {
+            default_network: None,
+            networks: networks
+                .into_iter()
+                .map(|(name, network)| (name.to_string(), network))
+                .collect(),
+        }
+    }
+}
+
+impl Config {
+    /// Load the built-in presets, extended with the networks of the given file.
+    pub fn load(path: Option<&Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let Some(path) = path else {
+            return Ok(Config::default());
+        };
+
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read config file {}: {err}", path.display()))?;
+        Self::from_toml(&content)
+            .map_err(|err| format!("Failed to parse config file {}: {err}", path.display()).into())
+    }
+
+    /// The built-in presets, extended with the networks of the given TOML content.
+    fn from_toml(content: &str) -> Result<Self, toml::de::Error> {
+        let mut config = Config::default();
+        let user: Config = toml::from_str(content)?;
+
+        if user.default_network.is_some() {
+            config.default_network = user.default_network;
+        }
+        config.networks.extend(user.networks);
+
+        Ok(config)
+    }
+
+    /// Resolve the network to monitor, applying the command line overrides.
+    ///
+    /// Fails when the metadata of its runtime is not compiled in.
+    pub fn network(
+        &self,
+        chain: Option<&str>,
+        overrides: NetworkOverrides,
+    ) -> Result<NetworkConfig, Box<dyn std::error::Error>> {
+        let network = self.network_without_metadata(chain, overrides)?;
+
+        if let (false, Some(feature)) = (network.runtime.is_available(), network.runtime.feature())
+        {
+            return Err(format!(
+                "Network {} requires the {:?} metadata. Please rebuild with `--features {feature}`.",
+                network.display_name, network.runtime
+            )
+            .into());
+        }
+
+        Ok(network)
+    }
+
+    /// Resolve the network like [`Config::network`], whether its metadata is compiled in or not,
+    /// for `update-metadata` to download it.
+    pub fn network_without_metadata(
+        &self,
+        chain: Option<&str>,
+        overrides: NetworkOverrides,
+    ) -> Result<NetworkConfig, Box<dyn std::error::Error>> {
+        let name = chain
+            .or(self.default_network.as_deref())
+            .unwrap_or(DEFAULT_NETWORK);
+
+        let Some(network) = self.networks.get(name) else {
+            let names = self
+                .networks
+                .keys()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(format!(
+                "Unsupported chain: {name}. Supported names are: {names}. \
+                 Please define it in the `--config` file or use `relay_chain_url` and `parachain_url` flags to specify custom URLs."
+            )
+            .into());
+        };
+
+        // An overridden endpoint replaces the fallbacks as well, they point at other nodes.
+        let mut network = network.clone();
+        if let Some(relay_chain_url) = overrides.relay_chain_url {
+            network.relay_chain_url = relay_chain_url;
+            network.relay_chain_fallback_urls.clear();
+        }
+        if let Some(parachain_url) = overrides.parachain_url {
+            network.parachain_url = parachain_url;
+            network.parachain_fallback_urls.clear();
+        }
+        if let Some(para_id) = overrides.para_id {
+            network.para_id = para_id;
+        }
+
+        Ok(network)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CUSTOM: &str = r#"
+        default_network = "my-parachain"
+
+        [networks.my-parachain]
+        display_name = "MyParachain"
+        relay_chain_url = "wss://relay.example.io"
+        parachain_url = "wss://para.example.io"
+        para_id = 2000
+        runtime = "kusama"
+        expected_block_time_ms = 2000
+
+        [networks.kusama-asset-hub]
+        display_name = "CustomAssetHub"
+        relay_chain_url = "wss://relay.example.io"
+        parachain_url = "wss://asset-hub.example.io"
+        para_id = 1000
+        runtime = "kusama"
+
+        [networks.kusama-asset-hub.thresholds]
+        slow_block_ms = 20000
+    "#;
+
+    #[test]
+    fn presets_without_file() {
+        let config = Config::load(None).unwrap();
+        let network = config.network(None, NetworkOverrides::default()).unwrap();
+
+        assert_eq!(network.display_name, "AssetHubKusama");
+        assert_eq!(network.slow_block_ms(), 12_000);
+        assert!(config.networks.contains_key("polkadot-asset-hub"));
+    }
+
+    #[test]
+    fn file_extends_and_replaces_presets() {
+        let config = Config::from_toml(CUSTOM).unwrap();
+
+        let network = config.network(None, NetworkOverrides::default()).unwrap();
+        assert_eq!(network.display_name, "MyParachain");
+        assert_eq!(network.para_id, 2000);
+
+        let network = config
+            .network(Some("kusama-asset-hub"), NetworkOverrides::default())
+            .unwrap();
+        assert_eq!(network.display_name, "CustomAssetHub");
+        assert!(network.relay_chain_fallback_urls.is_empty());
+
+        assert!(config.networks.contains_key("westend-asset-hub"));
+    }
+
+    #[test]
+    fn thresholds_follow_the_block_time() {
+        let config = Config::from_toml(CUSTOM).unwrap();
+
+        let network = config
+            .network(Some("my-parachain"), NetworkOverrides::default())
+            .unwrap();
+        assert_eq!(network.slow_block_ms(), 4_000);
+        assert_eq!(network.timestamp_limits(None).ahead_ms, 4_000);
+
+        let network = config
+            .network(Some("kusama-asset-hub"), NetworkOverrides::default())
+            .unwrap();
+        assert_eq!(network.slow_block_ms(), 20_000);
+    }
+
+    #[test]
+    fn timestamp_limits_follow_the_allowed_ancestry() {
+        let network = Config::default()
+            .network(None, NetworkOverrides::default())
+            .unwrap();
+
+        assert_eq!(
+            network.timestamp_limits(Some(2)),
+            TimestampLimits {
+                ahead_ms: 18_000,
+                lag_ms: 6_000
+            }
+        );
+    }
+
+    #[test]
+    fn overrides_apply_to_the_selected_network() {
+        let config = Config::default();
+        let network = config
+            .network(
+                Some("kusama-asset-hub"),
+                NetworkOverrides {
+                    relay_chain_url: None,
+                    parachain_url: Some("ws://localhost:9944".into()),
+                    para_id: Some(2000),
+                },
+            )
+            .unwrap();
+
+        assert_eq!(network.parachain_url, "ws://localhost:9944");
+        assert_eq!(network.para_id, 2000);
+        assert_eq!(network.relay_chain_url, "wss://rpc-kusama.helixstreet.io");
+    }
+
+    #[test]
+    fn overridden_urls_drop_their_fallbacks() {
+        let network = Config::default()
+            .network(
+                Some("kusama-asset-hub"),
+                NetworkOverrides {
+                    parachain_url: Some("ws://localhost:9944".into()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(network.parachain_urls(), ["ws://localhost:9944"]);
+        assert_eq!(
+            network.relay_chain_urls(),
+            [
+                "wss://rpc-kusama.helixstreet.io",
+                "wss://kusama-rpc.polkadot.io"
+            ]
+        );
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let reconnect = ReconnectConfig {
+            initial_backoff_ms: 500,
+            max_backoff_ms: 5_000,
+            max_backfill_blocks: 600,
+        };
+        let delays = (0..6)
+            .map(|attempt| reconnect.backoff(attempt).as_millis())
+            .collect::<Vec<_>>();
+
+        assert_eq!(delays, [500, 1_000, 2_000, 4_000, 5_000, 5_000]);
+        assert_eq!(reconnect.backoff(u32::MAX).as_millis(), 5_000);
+    }
+
+    #[test]
+    fn unknown_network_is_an_error() {
+        let err = Config::default()
+            .network(Some("unknown"), NetworkOverrides::default())
+            .unwrap_err();
+
+        assert!(err.to_string().contains("Unsupported chain: unknown"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "westend"))]
+    fn metadata_is_only_required_to_monitor() {
+        let config =
+            Config::from_toml(&CUSTOM.replace("runtime = \"kusama\"", "runtime = \"westend\""))
+                .unwrap();
+
+        assert!(
+            config
+                .network(Some("my-parachain"), NetworkOverrides::default())
+                .is_err()
+        );
+        assert!(
+            config
+                .network_without_metadata(Some("my-parachain"), NetworkOverrides::default())
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn polkadot_is_monitored_without_its_feature() {
+        let config = Config::load(None).unwrap();
+        let network = config
+            .network(Some("polkadot-asset-hub"), NetworkOverrides::default())
+            .unwrap();
+
+        assert_eq!(network.runtime, Runtime::Polkadot);
+        if !cfg!(feature = "polkadot") {
+            assert_eq!(network.runtime.fallback(), Some(Runtime::Kusama));
+        }
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let content = CUSTOM.replace("para_id = 2000", "para_id = 2000\n        paraid = 2000");
+
+        assert!(Config::from_toml(&content).is_err());
+    }
+}
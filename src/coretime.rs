@@ -0,0 +1,233 @@
+use std::fmt;
+
+use subxt::ext::scale_value::{Value, ValueDef};
+use subxt::{OnlineClient, PolkadotConfig};
+
+/// A change in the cores assigned to the parachain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoreChange {
+    /// The parachain got cores after having none.
+    Started(Vec<u32>),
+    /// The parachain lost all of its cores.
+    Ended(Vec<u32>),
+    Changed {
+        from: Vec<u32>,
+        to: Vec<u32>,
+    },
+}
+
+impl fmt::Display for CoreChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoreChange::Started(cores) => {
+                write!(f, "assignment started on cores {}", format_cores(cores))
+            }
+            CoreChange::Ended(cores) => write!(
+                f,
+                "assignment ended on cores {}, no core assigned",
+                format_cores(cores)
+            ),
+            CoreChange::Changed { from, to } => write!(
+                f,
+                "assignment changed from cores {} to {}",
+                format_cores(from),
+                format_cores(to)
+            ),
+        }
+    }
+}
+
+pub fn format_cores(cores: &[u32]) -> String {
+    let cores = cores
+        .iter()
+        .map(|core| core.to_string())
+        .collect::<Vec<_>>();
+
+    format!("[{}]", cores.join(", "))
+}
+
+/// The cores of `ParaScheduler::ClaimQueue` assigned to the parachain, over relay chain blocks.
+#[derive(Debug, Default)]
+pub struct CoreTracker {
+    cores: Vec<u32>,
+    /// The cores assigned through the on-demand pool at the last block.
+    pool_cores: Vec<u32>,
+}
+
+impl CoreTracker {
+    /// Record the cores assigned at a relay chain block.
+    ///
+    /// Returns the change against the previous block. The parachain is assumed to have
+    /// no core before the first block.
+    pub fn update(&mut self, mut cores: Vec<u32>) -> Option<CoreChange> {
+        cores.sort_unstable();
+
+        let previous = std::mem::replace(&mut self.cores, cores.clone());
+        match (previous.is_empty(), cores.is_empty()) {
+            _ if previous == cores => None,
+            (true, _) => Some(CoreChange::Started(cores)),
+            (_, true) => Some(CoreChange::Ended(previous)),
+            _ => Some(CoreChange::Changed {
+                from: previous,
+                to: cores,
+            }),
+        }
+    }
+
+    /// Record the cores assigned through the on-demand pool at a relay chain block.
+    ///
+    /// Returns the pool cores of the previous block, whose claims the candidates backed
+    /// in this block were scheduled on.
+    pub fn update_pool(&mut self, mut pool_cores: Vec<u32>) -> Vec<u32> {
+        pool_cores.sort_unstable();
+
+        std::mem::replace(&mut self.pool_cores, pool_cores)
+    }
+}
+
+/// Why the parachain did not produce blocks for several relay chain blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GapCause {
+    /// No core was assigned at `blocks` of the `sampled` relay chain blocks of the gap.
+    NoCore { blocks: u32, sampled: u32 },
+    /// A core was assigned throughout the gap, the collators or backers failed to use it.
+    Unused,
+}
+
+impl fmt::Display for GapCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GapCause::NoCore { blocks, sampled } => write!(
+                f,
+                "no core assigned at {blocks} of {sampled} sampled relay blocks"
+            ),
+            GapCause::Unused => {
+                f.write_str("core assigned throughout, collator or backing failure")
+            }
+        }
+    }
+}
+
+/// The relay chain blocks sampled in a gap between relay parents `from` and `to`:
+/// the first, the middle and the last block strictly between them.
+pub fn gap_samples(from: u32, to: u32) -> Vec<u32> {
+    if to <= from + 1 {
+        return Vec::new();
+    }
+
+    let mut samples = vec![from + 1, from + (to - from) / 2, to - 1];
+    samples.dedup();
+
+    samples
+}
+
+/// Fetch the cores whose current `Broker::Workload` assigns them to `para_id`.
+///
+/// The coretime chain is queried dynamically, as its metadata is not compiled in.
+pub async fn broker_cores(
+    api: &OnlineClient<PolkadotConfig>,
+    para_id: u32,
+) -> Result<Vec<u32>, Box<dyn std::error::Error>> {
+    let storage = api.storage().at_latest().await?;
+    let mut workloads = storage
+        .iter(subxt::dynamic::storage("Broker", "Workload", ()))
+        .await?;
+
+    let mut cores = Vec::new();
+    while let Some(workload) = workloads.next().await {
+        let workload = workload?;
+        if !assigns_task(&workload.value.to_value()?, para_id) {
+            continue;
+        }
+
+        // `Workload` is keyed by the `Twox64Concat` hashed `u16` core index.
+        let key = &workload.key_bytes[workload.key_bytes.len().saturating_sub(2)..];
+        if let Ok(core) = <[u8; 2]>::try_from(key) {
+            cores.push(u16::from_le_bytes(core) as u32);
+        }
+    }
+    cores.sort_unstable();
+
+    Ok(cores)
+}
+
+/// Whether a broker schedule contains a `CoreAssignment::Task` of `para_id`.
+fn assigns_task<T>(value: &Value<T>, para_id: u32) -> bool {
+    match &value.value {
+        ValueDef::Variant(variant) if variant.name == "Task" => variant
+            .values
+            .values()
+            .any(|task| task.as_u128() == Some(para_id as u128)),
+        ValueDef::Variant(variant) => variant
+            .values
+            .values()
+            .any(|value| assigns_task(value, para_id)),
+        ValueDef::Composite(composite) => {
+            composite.values().any(|value| assigns_task(value, para_id))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gaps_are_sampled_at_both_ends_and_the_middle() {
+        assert_eq!(gap_samples(10, 11), Vec::<u32>::new());
+        assert_eq!(gap_samples(10, 12), vec![11]);
+        assert_eq!(gap_samples(10, 13), vec![11, 12]);
+        assert_eq!(gap_samples(10, 30), vec![11, 20, 29]);
+    }
+
+    #[test]
+    fn first_assignment_starts() {
+        let mut tracker = CoreTracker::default();
+
+        assert_eq!(tracker.update(Vec::new()), None);
+        assert_eq!(
+            tracker.update(vec![3, 1]),
+            Some(CoreChange::Started(vec![1, 3]))
+        );
+    }
+
+    #[test]
+    fn unchanged_cores_in_any_order() {
+        let mut tracker = CoreTracker::default();
+        tracker.update(vec![1, 3]);
+
+        assert_eq!(tracker.update(vec![3, 1]), None);
+    }
+
+    #[test]
+    fn changed_and_ended_assignments() {
+        let mut tracker = CoreTracker::default();
+        tracker.update(vec![1]);
+
+        assert_eq!(
+            tracker.update(vec![2, 1]),
+            Some(CoreChange::Changed {
+                from: vec![1],
+                to: vec![1, 2]
+            })
+        );
+        assert_eq!(
+            tracker.update(Vec::new()),
+            Some(CoreChange::Ended(vec![1, 2]))
+        );
+        assert_eq!(
+            CoreChange::Ended(vec![1, 2]).to_string(),
+            "assignment ended on cores [1, 2], no core assigned"
+        );
+    }
+
+    #[test]
+    fn pool_cores_of_the_previous_block() {
+        let mut tracker = CoreTracker::default();
+
+        assert!(tracker.update_pool(vec![4, 2]).is_empty());
+        assert_eq!(tracker.update_pool(Vec::new()), vec![2, 4]);
+        assert!(tracker.update_pool(vec![2]).is_empty());
+    }
+}
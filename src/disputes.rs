@@ -0,0 +1,324 @@
+use std::collections::{BTreeMap, HashMap};
+
+use subxt::utils::H256;
+
+use crate::runtime::DisputeEvent;
+use crate::window::{Recent, Window, WindowConfig};
+
+/// A candidate of the parachain backed on the relay chain.
+#[derive(Debug, Clone, Copy)]
+pub struct Candidate {
+    /// The hash of the parachain block.
+    pub para_head: H256,
+    /// The relay chain block the candidate was backed in.
+    pub backed_at: u32,
+}
+
+/// A dispute raised against a candidate of the parachain.
+#[derive(Debug, Clone, Copy)]
+pub struct Dispute {
+    pub candidate_hash: H256,
+    pub candidate: Candidate,
+    /// The relay chain block the dispute was initiated in.
+    pub initiated_at: u32,
+    /// The dispute was raised by a validator of this chain, rather than imported.
+    pub local: bool,
+}
+
+/// How a dispute ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisputeOutcome {
+    Valid,
+    Invalid,
+    /// Not concluded within `dispute_timeout_blocks`.
+    TimedOut,
+}
+
+impl std::fmt::Display for DisputeOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            DisputeOutcome::Valid => "valid",
+            DisputeOutcome::Invalid => "INVALID",
+            DisputeOutcome::TimedOut => "timed out",
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// A change in the disputes of the parachain candidates.
+#[derive(Debug, Clone, Copy)]
+pub enum DisputeUpdate {
+    Initiated(Dispute),
+    Concluded(Dispute, DisputeOutcome),
+}
+
+/// Links `ParasDisputes` events to the candidates of the parachain.
+///
+/// The runtime no longer emits a timeout event, hence disputes still open after
+/// `timeout_blocks` relay chain blocks are reported as timed out.
+pub struct DisputeTracker {
+    /// Candidates backed within the window, by candidate hash.
+    candidates: HashMap<H256, Candidate>,
+    window: Window<H256>,
+    open: BTreeMap<H256, Dispute>,
+    timeout_blocks: u32,
+}
+
+impl DisputeTracker {
+    pub fn new(window: WindowConfig, timeout_blocks: u32) -> Self {
+        Self {
+            candidates: HashMap::new(),
+            window: Window::new(window),
+            open: BTreeMap::new(),
+            timeout_blocks,
+        }
+    }
+
+    /// Record a candidate of the parachain backed at relay chain block `number`.
+    pub fn record_candidate(&mut self, number: u32, candidate_hash: H256, para_head: H256) {
+        let candidates = &mut self.candidates;
+        self.window.prune(number, |_, candidate_hash| {
+            candidates.remove(&candidate_hash);
+        });
+
+        self.candidates.insert(
+            candidate_hash,
+            Candidate {
+                para_head,
+                backed_at: number,
+            },
+        );
+        self.window.push(number, candidate_hash);
+    }
+
+    /// Apply a dispute event of relay chain block `number`.
+    ///
+    /// Returns an update if the event concerns a candidate of the parachain.
+    pub fn on_event(&mut self, number: u32, event: &DisputeEvent) -> Option<DisputeUpdate> {
+        match *event {
+            DisputeEvent::Initiated {
+                candidate_hash,
+                local,
+            } => {
+                let candidate = *self.candidates.get(&candidate_hash)?;
+                let dispute = Dispute {
+                    candidate_hash,
+                    candidate,
+                    initiated_at: number,
+                    local,
+                };
+                self.open.insert(candidate_hash, dispute);
+
+                Some(DisputeUpdate::Initiated(dispute))
+            }
+            DisputeEvent::Concluded {
+                candidate_hash,
+                valid,
+            } => {
+                // Disputes initiated before the monitor started are concluded without being open.
+                let dispute = self.open.remove(&candidate_hash).or_else(|| {
+                    let candidate = *self.candidates.get(&candidate_hash)?;
+                    Some(Dispute {
+                        candidate_hash,
+                        candidate,
+                        initiated_at: number,
+                        local: false,
+                    })
+                })?;
+                let outcome = if valid {
+                    DisputeOutcome::Valid
+                } else {
+                    DisputeOutcome::Invalid
+                };
+
+                Some(DisputeUpdate::Concluded(dispute, outcome))
+            }
+            DisputeEvent::Revert { .. } => None,
+        }
+    }
+
+    /// Time out the disputes still open at relay chain block `number`.
+    pub fn expire(&mut self, number: u32) -> Vec<DisputeUpdate> {
+        let expired = self
+            .open
+            .values()
+            .filter(|dispute| number.saturating_sub(dispute.initiated_at) >= self.timeout_blocks)
+            .map(|dispute| dispute.candidate_hash)
+            .collect::<Vec<_>>();
+
+        expired
+            .into_iter()
+            .filter_map(|candidate_hash| self.open.remove(&candidate_hash))
+            .map(|dispute| DisputeUpdate::Concluded(dispute, DisputeOutcome::TimedOut))
+            .collect()
+    }
+}
+
+/// Disputes of the parachain candidates over a session.
+#[derive(Debug, Default)]
+pub struct DisputeStats {
+    pub initiated: u64,
+    pub valid: u64,
+    pub invalid: u64,
+    pub timed_out: u64,
+    /// Relay chain reverts caused by disputes of any parachain.
+    pub reverts: u64,
+    /// The last concluded disputes, in order.
+    concluded: Recent<DisputeUpdate>,
+}
+
+impl DisputeStats {
+    pub fn record(&mut self, update: &DisputeUpdate) {
+        match update {
+            DisputeUpdate::Initiated(_) => self.initiated += 1,
+            DisputeUpdate::Concluded(_, DisputeOutcome::Valid) => self.valid += 1,
+            DisputeUpdate::Concluded(_, DisputeOutcome::Invalid) => self.invalid += 1,
+            DisputeUpdate::Concluded(_, DisputeOutcome::TimedOut) => self.timed_out += 1,
+        }
+        if let DisputeUpdate::Concluded(..) = update {
+            self.concluded.push(*update);
+        }
+    }
+
+    /// Render the counters, followed by one line per recently concluded dispute.
+    pub fn render(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            " Disputes: initiated {} | valid {} | invalid {} | timed out {} | relay chain reverts {}",
+            self.initiated, self.valid, self.invalid, self.timed_out, self.reverts
+        )];
+
+        if self.concluded.forgotten() > 0 {
+            lines.push(format!(
+                "   ... {} earlier concluded disputes",
+                self.concluded.forgotten()
+            ));
+        }
+        for update in self.concluded.iter() {
+            if let DisputeUpdate::Concluded(dispute, outcome) = update {
+                lines.push(format!(
+                    "   candidate {:?} (para_head={:?}, backed at #{}): {outcome}",
+                    dispute.candidate_hash,
+                    dispute.candidate.para_head,
+                    dispute.candidate.backed_at
+                ));
+            }
+        }
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> DisputeTracker {
+        DisputeTracker::new(
+            WindowConfig {
+                blocks: Some(100),
+                seconds: None,
+            },
+            10,
+        )
+    }
+
+    fn hash(byte: u8) -> H256 {
+        H256::repeat_byte(byte)
+    }
+
+    fn initiated(candidate_hash: H256) -> DisputeEvent {
+        DisputeEvent::Initiated {
+            candidate_hash,
+            local: true,
+        }
+    }
+
+    fn concluded(candidate_hash: H256, valid: bool) -> DisputeEvent {
+        DisputeEvent::Concluded {
+            candidate_hash,
+            valid,
+        }
+    }
+
+    #[test]
+    fn initiated_then_concluded() {
+        let mut tracker = tracker();
+        tracker.record_candidate(100, hash(1), hash(11));
+
+        let Some(DisputeUpdate::Initiated(dispute)) = tracker.on_event(102, &initiated(hash(1)))
+        else {
+            panic!("dispute not initiated");
+        };
+        assert_eq!(dispute.candidate.para_head, hash(11));
+        assert_eq!(dispute.candidate.backed_at, 100);
+        assert!(dispute.local);
+
+        let Some(DisputeUpdate::Concluded(dispute, outcome)) =
+            tracker.on_event(104, &concluded(hash(1), false))
+        else {
+            panic!("dispute not concluded");
+        };
+        assert_eq!(outcome, DisputeOutcome::Invalid);
+        assert_eq!(dispute.initiated_at, 102);
+        assert!(tracker.expire(200).is_empty());
+    }
+
+    #[test]
+    fn concluded_without_initiation() {
+        let mut tracker = tracker();
+        tracker.record_candidate(100, hash(1), hash(11));
+
+        let Some(DisputeUpdate::Concluded(dispute, outcome)) =
+            tracker.on_event(104, &concluded(hash(1), true))
+        else {
+            panic!("dispute not concluded");
+        };
+        assert_eq!(outcome, DisputeOutcome::Valid);
+        assert_eq!(dispute.initiated_at, 104);
+        assert!(!dispute.local);
+    }
+
+    #[test]
+    fn other_candidates_are_ignored() {
+        let mut tracker = tracker();
+        tracker.record_candidate(100, hash(1), hash(11));
+
+        assert!(tracker.on_event(102, &initiated(hash(2))).is_none());
+        assert!(tracker.on_event(103, &concluded(hash(2), false)).is_none());
+        assert!(
+            tracker
+                .on_event(104, &DisputeEvent::Revert { block: 90 })
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn open_disputes_time_out() {
+        let mut tracker = tracker();
+        tracker.record_candidate(100, hash(1), hash(11));
+        tracker.on_event(102, &initiated(hash(1)));
+
+        assert!(tracker.expire(111).is_empty());
+
+        let expired = tracker.expire(112);
+        assert_eq!(expired.len(), 1);
+        assert!(matches!(
+            expired[0],
+            DisputeUpdate::Concluded(dispute, DisputeOutcome::TimedOut) if dispute.candidate_hash == hash(1)
+        ));
+        assert!(tracker.expire(200).is_empty());
+    }
+
+    #[test]
+    fn candidates_outside_the_window_are_forgotten() {
+        let mut tracker = tracker();
+        tracker.record_candidate(100, hash(1), hash(11));
+        tracker.record_candidate(150, hash(2), hash(12));
+        tracker.record_candidate(200, hash(3), hash(13));
+
+        assert!(tracker.on_event(200, &initiated(hash(1))).is_none());
+        assert!(tracker.on_event(200, &initiated(hash(2))).is_some());
+        assert!(tracker.on_event(200, &initiated(hash(3))).is_some());
+    }
+}
@@ -0,0 +1,151 @@
+use crate::author::{AuthorExtractor, BlockAuthor, ConsensusEngine};
+
+/// The likely root cause of a block repeating the timestamp of an earlier block.
+///
+/// Variants are ordered by priority: the first matching cause is the primary one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DuplicateCause {
+    /// Several blocks exist at the height of the origin block.
+    CompetingFork,
+    /// The relay parent is older than the relay parent of the origin block.
+    RelayParentRegression,
+    /// The same collator authored both blocks in the same slot of the consensus engine.
+    SameSlot(ConsensusEngine),
+    /// The same collator authored both blocks, for engines without slots in their digest.
+    SameAuthor(ConsensusEngine),
+    /// Both blocks were built on the same relay parent.
+    SameRelayParent,
+    /// None of the known causes apply.
+    Unknown,
+}
+
+impl std::fmt::Display for DuplicateCause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DuplicateCause::CompetingFork => f.write_str("competing fork at the origin height"),
+            DuplicateCause::RelayParentRegression => f.write_str("relay parent regression"),
+            DuplicateCause::SameSlot(engine) => {
+                write!(f, "same {engine} slot by the same collator")
+            }
+            DuplicateCause::SameAuthor(engine) => write!(f, "same {engine} author"),
+            DuplicateCause::SameRelayParent => f.write_str("same relay parent reused"),
+            DuplicateCause::Unknown => f.write_str("unknown"),
+        }
+    }
+}
+
+/// What is known about one of the two blocks sharing a timestamp.
+#[derive(Debug, Clone)]
+pub struct BlockInfo {
+    pub relay_parent: Option<u32>,
+    pub author: BlockAuthor,
+}
+
+/// Classify a duplicated timestamp, most likely cause first.
+///
+/// `forks_at_origin` is the number of blocks known at the height of the origin block.
+/// Both authors were read by `extractor`; their slots are compared when the digest carries one.
+pub fn classify(
+    extractor: &AuthorExtractor,
+    origin: &BlockInfo,
+    duplicate: &BlockInfo,
+    forks_at_origin: usize,
+) -> Vec<DuplicateCause> {
+    let mut causes = Vec::new();
+
+    if forks_at_origin > 1 {
+        causes.push(DuplicateCause::CompetingFork);
+    }
+
+    if let (Some(origin_parent), Some(parent)) = (origin.relay_parent, duplicate.relay_parent) {
+        if parent < origin_parent {
+            causes.push(DuplicateCause::RelayParentRegression);
+        }
+        if parent == origin_parent {
+            causes.push(DuplicateCause::SameRelayParent);
+        }
+    }
+
+    if origin.author.id == duplicate.author.id {
+        let engine = extractor.engine();
+        match (origin.author.slot, duplicate.author.slot) {
+            (Some(origin_slot), Some(slot)) if origin_slot == slot => {
+                causes.push(DuplicateCause::SameSlot(engine))
+            }
+            (None, None) => causes.push(DuplicateCause::SameAuthor(engine)),
+            _ => {}
+        }
+    }
+
+    if causes.is_empty() {
+        causes.push(DuplicateCause::Unknown);
+    }
+    causes.sort();
+
+    causes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(relay_parent: u32, id: &[u8], slot: Option<u64>) -> BlockInfo {
+        BlockInfo {
+            relay_parent: Some(relay_parent),
+            author: BlockAuthor {
+                id: id.to_vec(),
+                slot,
+            },
+        }
+    }
+
+    #[test]
+    fn causes_are_sorted_by_priority() {
+        let origin = block(100, b"a", Some(1));
+        let duplicate = block(100, b"a", Some(1));
+
+        assert_eq!(
+            classify(&AuthorExtractor::Aura, &origin, &duplicate, 2),
+            vec![
+                DuplicateCause::CompetingFork,
+                DuplicateCause::SameSlot(AuthorExtractor::Aura.engine()),
+                DuplicateCause::SameRelayParent,
+            ]
+        );
+    }
+
+    #[test]
+    fn relay_parent_regression() {
+        let origin = block(100, b"a", Some(1));
+        let duplicate = block(99, b"b", Some(2));
+
+        assert_eq!(
+            classify(&AuthorExtractor::Aura, &origin, &duplicate, 1),
+            vec![DuplicateCause::RelayParentRegression]
+        );
+    }
+
+    #[test]
+    fn authors_without_slots_are_compared_by_id() {
+        let origin = block(100, b"collator", None);
+        let duplicate = block(101, b"collator", None);
+
+        let causes = classify(&AuthorExtractor::Nimbus, &origin, &duplicate, 1);
+        assert_eq!(
+            causes,
+            vec![DuplicateCause::SameAuthor(AuthorExtractor::Nimbus.engine())]
+        );
+        assert_eq!(causes[0].to_string(), "same nmbs author");
+    }
+
+    #[test]
+    fn different_slots_of_the_same_author_are_not_a_cause() {
+        let origin = block(100, b"collator", Some(1));
+        let duplicate = block(101, b"collator", Some(2));
+
+        assert_eq!(
+            classify(&AuthorExtractor::Aura, &origin, &duplicate, 1),
+            vec![DuplicateCause::Unknown]
+        );
+    }
+}
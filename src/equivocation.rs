@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use subxt::utils::H256;
+
+use crate::author::BlockAuthor;
+use crate::config::NetworkConfig;
+use crate::window::{Window, WindowConfig};
+
+/// A parachain block, with its SCALE encoded header as evidence.
+#[derive(Debug, Clone)]
+pub struct EvidenceBlock {
+    pub hash: H256,
+    pub header: Vec<u8>,
+}
+
+/// Two distinct blocks authored at the same parachain height with the same pre-runtime digest
+/// payload: the slot for Aura, the author key for other engines.
+#[derive(Debug, Clone)]
+pub struct Equivocation {
+    pub number: u32,
+    pub author: BlockAuthor,
+    pub first: EvidenceBlock,
+    pub second: EvidenceBlock,
+}
+
+impl fmt::Display for Equivocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "block #{} authored twice by {}: {:?} and {:?}",
+            self.number, self.author, self.first.hash, self.second.hash
+        )
+    }
+}
+
+/// The evidence bundle written for an equivocation.
+#[derive(Debug, Serialize)]
+struct Evidence<'a> {
+    network: &'a str,
+    para_id: u32,
+    number: u32,
+    slot: Option<u64>,
+    /// The pre-runtime digest payload identifying the author.
+    author: String,
+    blocks: Vec<EvidenceEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct EvidenceEntry {
+    hash: String,
+    header: String,
+}
+
+impl Equivocation {
+    /// Write the evidence bundle to a TOML file of `dir`, returning its path.
+    pub fn export(
+        &self,
+        network: &NetworkConfig,
+        dir: &Path,
+    ) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let evidence = Evidence {
+            network: &network.display_name,
+            para_id: network.para_id,
+            number: self.number,
+            slot: self.author.slot,
+            author: format!("0x{}", hex::encode(&self.author.id)),
+            blocks: [&self.first, &self.second]
+                .into_iter()
+                .map(|block| EvidenceEntry {
+                    hash: format!("{:?}", block.hash),
+                    header: format!("0x{}", hex::encode(&block.header)),
+                })
+                .collect(),
+        };
+
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(evidence_file_name(
+            network.para_id,
+            self.number,
+            self.second.hash,
+        ));
+        std::fs::write(&path, toml::to_string(&evidence)?)?;
+
+        Ok(path)
+    }
+}
+
+/// The evidence file name, built from numbers only so it is safe on every file system.
+fn evidence_file_name(para_id: u32, number: u32, hash: H256) -> String {
+    format!("para-{para_id}-{number}-{hash:?}.toml")
+}
+
+/// The blocks seen at each height and author, to find distinct blocks of the same author.
+pub struct EquivocationDetector {
+    blocks: HashMap<(u32, Vec<u8>), Vec<EvidenceBlock>>,
+    window: Window<(u32, Vec<u8>)>,
+}
+
+impl EquivocationDetector {
+    pub fn new(config: WindowConfig) -> Self {
+        Self {
+            blocks: HashMap::new(),
+            window: Window::new(config),
+        }
+    }
+
+    /// Record a block, returning the equivocation if the same author already built
+    /// another block at this height.
+    ///
+    /// The author ID of Aura is the slot, so Aura blocks are compared by slot.
+    pub fn record(
+        &mut self,
+        number: u32,
+        author: &BlockAuthor,
+        block: EvidenceBlock,
+    ) -> Option<Equivocation> {
+        let blocks = &mut self.blocks;
+        self.window.prune(number, |_, key| {
+            blocks.remove(&key);
+        });
+
+        let key = (number, author.id.clone());
+        let seen = match self.blocks.get_mut(&key) {
+            Some(seen) => seen,
+            None => {
+                self.window.push(number, key.clone());
+                self.blocks.entry(key).or_default()
+            }
+        };
+        // The best block subscription reports a block again when a fork becomes best.
+        if seen.iter().any(|seen| seen.hash == block.hash) {
+            return None;
+        }
+
+        let equivocation = seen.first().map(|first| Equivocation {
+            number,
+            author: author.clone(),
+            first: first.clone(),
+            second: block.clone(),
+        });
+        seen.push(block);
+
+        equivocation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector() -> EquivocationDetector {
+        EquivocationDetector::new(WindowConfig {
+            blocks: Some(10),
+            seconds: None,
+        })
+    }
+
+    fn author(slot: u64) -> BlockAuthor {
+        BlockAuthor {
+            id: slot.to_le_bytes().to_vec(),
+            slot: Some(slot),
+        }
+    }
+
+    fn block(byte: u8) -> EvidenceBlock {
+        EvidenceBlock {
+            hash: H256::repeat_byte(byte),
+            header: vec![byte],
+        }
+    }
+
+    #[test]
+    fn two_blocks_of_the_same_author_at_the_same_height() {
+        let mut detector = detector();
+
+        assert!(detector.record(5, &author(1), block(1)).is_none());
+        let equivocation = detector.record(5, &author(1), block(2)).unwrap();
+        assert_eq!(equivocation.number, 5);
+        assert_eq!(equivocation.first.hash, H256::repeat_byte(1));
+        assert_eq!(equivocation.second.hash, H256::repeat_byte(2));
+    }
+
+    #[test]
+    fn the_same_block_seen_twice() {
+        let mut detector = detector();
+        detector.record(5, &author(1), block(1));
+
+        assert!(detector.record(5, &author(1), block(1)).is_none());
+    }
+
+    #[test]
+    fn different_authors_or_heights() {
+        let mut detector = detector();
+        detector.record(5, &author(1), block(1));
+
+        assert!(detector.record(5, &author(2), block(2)).is_none());
+        assert!(detector.record(6, &author(1), block(3)).is_none());
+    }
+
+    #[test]
+    fn heights_out_of_the_window_are_forgotten() {
+        let mut detector = detector();
+        detector.record(5, &author(1), block(1));
+        detector.record(15, &author(2), block(2));
+
+        assert!(detector.blocks.keys().all(|(number, _)| *number == 15));
+    }
+
+    #[test]
+    fn evidence_file_name_is_built_from_numbers() {
+        let name = evidence_file_name(1000, 5, H256::repeat_byte(0xab));
+
+        assert_eq!(name, format!("para-1000-5-0x{}.toml", "ab".repeat(32)));
+        assert!(
+            name.chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+        );
+    }
+}
@@ -0,0 +1,631 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use subxt::PolkadotConfig;
+use subxt::ext::futures::future::join_all;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::config::NetworkConfig;
+use crate::runtime::RELAY_CHAIN_SLOT_DURATION_MS;
+
+/// Endpoints not answering within this delay are reported as unreachable.
+const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Periodic health checks of every configured RPC endpoint.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct HealthConfig {
+    /// Seconds between two checks, disabled when 0. The endpoints are probed concurrently.
+    pub interval_secs: u64,
+
+    /// Endpoints whose best block is further behind the best endpoint are flagged.
+    pub max_lag_blocks: u32,
+
+    /// Endpoints with fewer peers are flagged.
+    pub min_peers: usize,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 60,
+            max_lag_blocks: 3,
+            min_peers: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Chain {
+    Relay,
+    Parachain,
+}
+
+/// A problem of an endpoint, or of the chain when all endpoints agree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthIssue {
+    Unreachable {
+        url: String,
+        error: String,
+    },
+    NoPeers {
+        url: String,
+        peers: usize,
+    },
+    Syncing {
+        url: String,
+        current: u32,
+        highest: u32,
+    },
+    /// The best block of the endpoint is behind the best block of the other endpoints.
+    Behind {
+        url: String,
+        best: u32,
+        blocks: u32,
+    },
+    /// The finalized block of the endpoint is behind the other endpoints.
+    FinalityBehind {
+        url: String,
+        finalized: u32,
+        blocks: u32,
+    },
+    /// The chain produced far fewer blocks than expected, on every endpoint.
+    ChainSlow {
+        blocks: u32,
+        expected: u32,
+        secs: u64,
+    },
+}
+
+impl HealthIssue {
+    /// The endpoint the issue is attributed to, `None` for the chain itself.
+    pub fn url(&self) -> Option<&str> {
+        match self {
+            HealthIssue::Unreachable { url, .. }
+            | HealthIssue::NoPeers { url, .. }
+            | HealthIssue::Syncing { url, .. }
+            | HealthIssue::Behind { url, .. }
+            | HealthIssue::FinalityBehind { url, .. } => Some(url),
+            HealthIssue::ChainSlow { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for HealthIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HealthIssue::Unreachable { url, error } => {
+                write!(f, "RPC node {url} is unreachable: {error}")
+            }
+            HealthIssue::NoPeers { url, peers } => {
+                write!(f, "RPC node {url} has {peers} peers")
+            }
+            HealthIssue::Syncing {
+                url,
+                current,
+                highest,
+            } => write!(f, "RPC node {url} is syncing: #{current} of #{highest}"),
+            HealthIssue::Behind { url, best, blocks } => write!(
+                f,
+                "RPC node {url} is slow: best #{best}, {blocks} blocks behind the other endpoints"
+            ),
+            HealthIssue::FinalityBehind {
+                url,
+                finalized,
+                blocks,
+            } => write!(
+                f,
+                "RPC node {url} is slow to finalize: finalized #{finalized}, {blocks} blocks behind the other endpoints"
+            ),
+            HealthIssue::ChainSlow {
+                blocks,
+                expected,
+                secs,
+            } => write!(
+                f,
+                "the chain is slow: {blocks} blocks in {secs}s, expected {expected} (all endpoints agree)"
+            ),
+        }
+    }
+}
+
+/// The outcome of a health check of the endpoints of a chain.
+#[derive(Debug, Clone)]
+pub struct HealthReport {
+    pub chain: Chain,
+    pub issues: Vec<HealthIssue>,
+}
+
+impl HealthReport {
+    /// Whether the endpoint at `url` fell behind or failed.
+    pub fn endpoint_unhealthy(&self, url: &str) -> bool {
+        self.issues.iter().any(|issue| issue.url() == Some(url))
+    }
+
+    /// Whether the chain itself is slow.
+    pub fn chain_slow(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| matches!(issue, HealthIssue::ChainSlow { .. }))
+    }
+}
+
+/// The state of an endpoint, as reported by the node.
+struct EndpointStatus {
+    peers: usize,
+    should_have_peers: bool,
+    is_syncing: bool,
+    current: u32,
+    highest: u32,
+    best: u32,
+    /// When the endpoint reported its best block.
+    best_at: Instant,
+    finalized: u32,
+}
+
+/// The `system_syncState` response.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SyncState {
+    current_block: u32,
+    highest_block: u32,
+}
+
+struct Endpoint {
+    url: String,
+    client: Option<subxt_rpcs::RpcClient>,
+}
+
+impl Endpoint {
+    async fn status(&mut self) -> Result<EndpointStatus, Box<dyn std::error::Error>> {
+        let client = match &self.client {
+            Some(client) => client.clone(),
+            None => {
+                let client = subxt_rpcs::RpcClient::from_url(self.url.as_str()).await?;
+                self.client = Some(client.clone());
+                client
+            }
+        };
+        let legacy_methods: subxt_rpcs::LegacyRpcMethods<PolkadotConfig> =
+            subxt_rpcs::LegacyRpcMethods::new(client.clone());
+
+        let health = legacy_methods.system_health().await?;
+        let sync_state: SyncState = client
+            .request("system_syncState", subxt_rpcs::rpc_params![])
+            .await?;
+        let best = legacy_methods
+            .chain_get_header(None)
+            .await?
+            .ok_or("Best header not found")?
+            .number;
+        let best_at = Instant::now();
+        let finalized_hash = legacy_methods.chain_get_finalized_head().await?;
+        let finalized = legacy_methods
+            .chain_get_header(Some(finalized_hash))
+            .await?
+            .ok_or("Finalized header not found")?
+            .number;
+
+        Ok(EndpointStatus {
+            peers: health.peers,
+            should_have_peers: health.should_have_peers,
+            is_syncing: health.is_syncing,
+            current: sync_state.current_block,
+            highest: sync_state.highest_block,
+            best,
+            best_at,
+            finalized,
+        })
+    }
+}
+
+/// The endpoints of a chain and the best block observed at the previous check.
+struct ChainHealth {
+    chain: Chain,
+    endpoints: Vec<Endpoint>,
+    block_time_ms: u64,
+    last_best: Option<(Instant, u32)>,
+}
+
+impl ChainHealth {
+    fn new(chain: Chain, urls: Vec<&str>, block_time_ms: u64) -> Self {
+        Self {
+            chain,
+            endpoints: urls
+                .into_iter()
+                .map(|url| Endpoint {
+                    url: url.to_string(),
+                    client: None,
+                })
+                .collect(),
+            block_time_ms,
+            last_best: None,
+        }
+    }
+
+    async fn check(&mut self, config: &HealthConfig) -> HealthReport {
+        let mut issues = Vec::new();
+
+        // All endpoints are probed at once, so an unreachable one does not delay the others.
+        let probes = join_all(self.endpoints.iter_mut().map(|endpoint| async move {
+            let status = match tokio::time::timeout(QUERY_TIMEOUT, endpoint.status()).await {
+                Ok(status) => status.map_err(|err| err.to_string()),
+                Err(_) => Err(format!("no answer within {QUERY_TIMEOUT:?}")),
+            };
+            (endpoint, status)
+        }))
+        .await;
+
+        let mut statuses = Vec::new();
+        for (endpoint, status) in probes {
+            let status = match status {
+                Ok(status) => status,
+                Err(error) => {
+                    // Reconnect at the next check.
+                    endpoint.client = None;
+                    issues.push(HealthIssue::Unreachable {
+                        url: endpoint.url.clone(),
+                        error,
+                    });
+                    continue;
+                }
+            };
+            statuses.push((endpoint.url.clone(), status));
+        }
+
+        let (compared, last_best) =
+            compare_statuses(&statuses, self.last_best, self.block_time_ms, config);
+        issues.extend(compared);
+        self.last_best = last_best;
+
+        HealthReport {
+            chain: self.chain,
+            issues,
+        }
+    }
+}
+
+/// Blocks expected to be produced during `duration`.
+fn blocks_in(duration: Duration, block_time_ms: u64) -> u32 {
+    (duration.as_millis() as u64 / block_time_ms.max(1)) as u32
+}
+
+/// Compare the statuses of the reachable endpoints of a chain, each taken at its own time,
+/// and their best block with the one of the previous check.
+///
+/// Returns the issues found and the best block to compare with at the next check.
+fn compare_statuses(
+    statuses: &[(String, EndpointStatus)],
+    last_best: Option<(Instant, u32)>,
+    block_time_ms: u64,
+    config: &HealthConfig,
+) -> (Vec<HealthIssue>, Option<(Instant, u32)>) {
+    let mut issues = Vec::new();
+
+    let best = statuses
+        .iter()
+        .map(|(_, status)| (status.best, status.best_at))
+        .max_by_key(|(best, _)| *best);
+    let finalized = statuses.iter().map(|(_, status)| status.finalized).max();
+    for (url, status) in statuses {
+        if status.should_have_peers && status.peers < config.min_peers {
+            issues.push(HealthIssue::NoPeers {
+                url: url.clone(),
+                peers: status.peers,
+            });
+        }
+        if status.is_syncing {
+            issues.push(HealthIssue::Syncing {
+                url: url.clone(),
+                current: status.current,
+                highest: status.highest,
+            });
+        }
+        // Blocks produced between both answers are not a lag of this endpoint.
+        if let Some((best, best_at)) = best
+            && best - status.best
+                > config.max_lag_blocks
+                    + blocks_in(
+                        best_at.saturating_duration_since(status.best_at),
+                        block_time_ms,
+                    )
+        {
+            issues.push(HealthIssue::Behind {
+                url: url.clone(),
+                best: status.best,
+                blocks: best - status.best,
+            });
+        }
+        if let Some(finalized) = finalized
+            && finalized - status.finalized > config.max_lag_blocks
+        {
+            issues.push(HealthIssue::FinalityBehind {
+                url: url.clone(),
+                finalized: status.finalized,
+                blocks: finalized - status.finalized,
+            });
+        }
+    }
+
+    // The best block of all endpoints only lags when the chain itself is slow.
+    if let Some((best, best_at)) = best
+        && let Some((checked_at, last_best)) = last_best
+    {
+        let elapsed = best_at.saturating_duration_since(checked_at);
+        let blocks = best.saturating_sub(last_best);
+        let expected = blocks_in(elapsed, block_time_ms);
+        if expected >= 2 && blocks * 2 < expected {
+            issues.push(HealthIssue::ChainSlow {
+                blocks,
+                expected,
+                secs: elapsed.as_secs(),
+            });
+        }
+    }
+
+    (
+        issues,
+        best.map(|(best, best_at)| (best_at, best)).or(last_best),
+    )
+}
+
+/// Check the health of every endpoint of the network at the configured interval.
+///
+/// Returns once the monitor stops receiving reports.
+pub async fn watch(network: NetworkConfig, reports: UnboundedSender<HealthReport>) {
+    let config = network.health;
+    let mut interval = tokio::time::interval(Duration::from_secs(config.interval_secs.max(1)));
+
+    let mut parachain_urls = network.parachain_urls();
+    parachain_urls.extend(network.parachain_observer_urls.iter().map(String::as_str));
+    let mut chains = [
+        ChainHealth::new(
+            Chain::Relay,
+            network.relay_chain_urls(),
+            RELAY_CHAIN_SLOT_DURATION_MS,
+        ),
+        ChainHealth::new(
+            Chain::Parachain,
+            parachain_urls,
+            network.expected_block_time_ms,
+        ),
+    ];
+
+    loop {
+        interval.tick().await;
+
+        for chain in &mut chains {
+            let report = chain.check(&config).await;
+            if reports.send(report).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Health issues over a session.
+#[derive(Debug, Default)]
+pub struct HealthStats {
+    /// Issues per endpoint.
+    endpoints: BTreeMap<String, u64>,
+    /// Checks where the chain itself was slow.
+    slow_chain: BTreeMap<Chain, u64>,
+}
+
+impl HealthStats {
+    pub fn record(&mut self, report: &HealthReport) {
+        for issue in &report.issues {
+            match issue.url() {
+                Some(url) => *self.endpoints.entry(url.to_string()).or_insert(0) += 1,
+                None => *self.slow_chain.entry(report.chain).or_insert(0) += 1,
+            }
+        }
+    }
+
+    pub fn render(&self) -> Vec<String> {
+        if self.endpoints.is_empty() && self.slow_chain.is_empty() {
+            return Vec::new();
+        }
+
+        let mut lines = vec![format!(
+            " Endpoint health: relay chain slow {} | parachain slow {} checks",
+            self.slow_chain.get(&Chain::Relay).unwrap_or(&0),
+            self.slow_chain.get(&Chain::Parachain).unwrap_or(&0)
+        )];
+        lines.extend(
+            self.endpoints
+                .iter()
+                .map(|(url, issues)| format!("   {url}: {issues} issues")),
+        );
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCK_TIME_MS: u64 = 6000;
+
+    fn status(best: u32, best_at: Instant, finalized: u32) -> EndpointStatus {
+        EndpointStatus {
+            peers: 10,
+            should_have_peers: true,
+            is_syncing: false,
+            current: best,
+            highest: best,
+            best,
+            best_at,
+            finalized,
+        }
+    }
+
+    fn compare(
+        statuses: Vec<(&str, EndpointStatus)>,
+        last_best: Option<(Instant, u32)>,
+    ) -> (Vec<HealthIssue>, Option<(Instant, u32)>) {
+        let statuses = statuses
+            .into_iter()
+            .map(|(url, status)| (url.to_string(), status))
+            .collect::<Vec<_>>();
+
+        compare_statuses(
+            &statuses,
+            last_best,
+            BLOCK_TIME_MS,
+            &HealthConfig::default(),
+        )
+    }
+
+    #[test]
+    fn blocks_produced_between_answers_are_not_a_lag() {
+        let now = Instant::now();
+        let later = now + Duration::from_secs(12);
+
+        // Answered 2 blocks earlier, 4 blocks behind: within the allowed lag of 3.
+        let (issues, _) = compare(
+            vec![("a", status(110, later, 100)), ("b", status(106, now, 100))],
+            None,
+        );
+        assert!(issues.is_empty());
+
+        let (issues, _) = compare(
+            vec![
+                ("a", status(110, later, 100)),
+                ("b", status(106, later, 100)),
+            ],
+            None,
+        );
+        assert_eq!(
+            issues,
+            vec![HealthIssue::Behind {
+                url: "b".into(),
+                best: 106,
+                blocks: 4
+            }]
+        );
+    }
+
+    #[test]
+    fn finality_behind_the_other_endpoints() {
+        let now = Instant::now();
+
+        let (issues, _) = compare(
+            vec![
+                ("a", status(110, now, 100)),
+                ("b", status(110, now, 97)),
+                ("c", status(110, now, 96)),
+            ],
+            None,
+        );
+        assert_eq!(
+            issues,
+            vec![HealthIssue::FinalityBehind {
+                url: "c".into(),
+                finalized: 96,
+                blocks: 4
+            }]
+        );
+    }
+
+    #[test]
+    fn missing_peers_only_when_expected() {
+        let now = Instant::now();
+        let mut lonely = status(110, now, 100);
+        lonely.peers = 0;
+        let mut dev = status(110, now, 100);
+        dev.peers = 0;
+        dev.should_have_peers = false;
+
+        let (issues, _) = compare(vec![("a", lonely), ("b", dev)], None);
+        assert_eq!(
+            issues,
+            vec![HealthIssue::NoPeers {
+                url: "a".into(),
+                peers: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn chain_slow_when_every_endpoint_lags() {
+        let now = Instant::now();
+        let later = now + Duration::from_secs(60);
+
+        let (issues, last_best) = compare(
+            vec![
+                ("a", status(102, later, 100)),
+                ("b", status(101, later, 100)),
+            ],
+            Some((now, 100)),
+        );
+        assert_eq!(
+            issues,
+            vec![HealthIssue::ChainSlow {
+                blocks: 2,
+                expected: 10,
+                secs: 60
+            }]
+        );
+        assert_eq!(last_best, Some((later, 102)));
+
+        let (issues, _) = compare(vec![("a", status(106, later, 100))], Some((now, 100)));
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn last_best_is_kept_without_answers() {
+        let now = Instant::now();
+
+        assert_eq!(
+            compare(Vec::new(), Some((now, 100))),
+            (Vec::new(), Some((now, 100)))
+        );
+        assert_eq!(compare(Vec::new(), None), (Vec::new(), None));
+    }
+
+    #[test]
+    fn stats_count_issues_per_endpoint_and_slow_chain() {
+        let mut stats = HealthStats::default();
+        assert!(stats.render().is_empty());
+
+        stats.record(&HealthReport {
+            chain: Chain::Parachain,
+            issues: vec![
+                HealthIssue::Unreachable {
+                    url: "b".into(),
+                    error: "timeout".into(),
+                },
+                HealthIssue::NoPeers {
+                    url: "a".into(),
+                    peers: 0,
+                },
+                HealthIssue::ChainSlow {
+                    blocks: 2,
+                    expected: 10,
+                    secs: 60,
+                },
+            ],
+        });
+        stats.record(&HealthReport {
+            chain: Chain::Relay,
+            issues: vec![HealthIssue::Behind {
+                url: "b".into(),
+                best: 10,
+                blocks: 5,
+            }],
+        });
+
+        assert_eq!(
+            stats.render(),
+            vec![
+                " Endpoint health: relay chain slow 0 | parachain slow 1 checks",
+                "   a: 1 issues",
+                "   b: 2 issues",
+            ]
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use crate::runtime::{Extrinsics, Runtime};
+
+const SET_VALIDATION_DATA: &str = "ParachainSystem.set_validation_data";
+const TIMESTAMP_SET: &str = "Timestamp.set";
+
+/// An unexpected layout of the parachain inherents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InherentIssue {
+    /// The inherent is missing, or could not be decoded.
+    Missing(&'static str),
+    /// The inherent is present more than once.
+    Duplicated(&'static str, usize),
+    /// `Timestamp.set` comes before `ParachainSystem.set_validation_data`.
+    OutOfOrder {
+        validation_data_index: u32,
+        timestamp_index: u32,
+    },
+}
+
+impl std::fmt::Display for InherentIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InherentIssue::Missing(call) => write!(f, "Missing {call}"),
+            InherentIssue::Duplicated(call, count) => {
+                write!(f, "Duplicated {call} ({count} times)")
+            }
+            InherentIssue::OutOfOrder {
+                validation_data_index,
+                timestamp_index,
+            } => write!(
+                f,
+                "Out of order: {TIMESTAMP_SET} (#{timestamp_index}) before {SET_VALIDATION_DATA} (#{validation_data_index})"
+            ),
+        }
+    }
+}
+
+/// The parachain inherents of a block, located by pallet and call.
+#[derive(Debug, Default)]
+pub struct Inherents {
+    /// The relay parent number of the first `ParachainSystem.set_validation_data`.
+    pub relay_parent_number: Option<u32>,
+    /// The value of the first `Timestamp.set`.
+    pub timestamp: Option<u64>,
+    /// The encoded first `Timestamp.set` extrinsic.
+    pub timestamp_bytes: Option<Vec<u8>>,
+    pub issues: Vec<InherentIssue>,
+}
+
+impl Inherents {
+    /// Locate the inherents anywhere in the block.
+    pub fn locate(runtime: &Runtime, extrinsics: &Extrinsics) -> Self {
+        let mut validation_data = Vec::new();
+        let mut timestamps = Vec::new();
+
+        for ext in extrinsics.iter() {
+            if let Some(relay_parent_number) = runtime.relay_parent_number(&ext) {
+                validation_data.push((ext.index(), relay_parent_number));
+            } else if let Some(timestamp) = runtime.timestamp(&ext) {
+                timestamps.push((ext.index(), timestamp, ext.bytes().to_vec()));
+            }
+        }
+
+        let issues = find_issues(
+            &validation_data
+                .iter()
+                .map(|(index, _)| *index)
+                .collect::<Vec<_>>(),
+            &timestamps
+                .iter()
+                .map(|(index, ..)| *index)
+                .collect::<Vec<_>>(),
+        );
+
+        let first_timestamp = timestamps.into_iter().next();
+        Self {
+            relay_parent_number: validation_data.first().map(|(_, number)| *number),
+            timestamp: first_timestamp.as_ref().map(|(_, timestamp, _)| *timestamp),
+            timestamp_bytes: first_timestamp.map(|(_, _, bytes)| bytes),
+            issues,
+        }
+    }
+}
+
+/// Check the extrinsic indices of both inherents for a missing, duplicated or misordered inherent.
+fn find_issues(validation_data: &[u32], timestamps: &[u32]) -> Vec<InherentIssue> {
+    let mut issues = Vec::new();
+    for (call, count) in [
+        (SET_VALIDATION_DATA, validation_data.len()),
+        (TIMESTAMP_SET, timestamps.len()),
+    ] {
+        match count {
+            0 => issues.push(InherentIssue::Missing(call)),
+            1 => (),
+            count => issues.push(InherentIssue::Duplicated(call, count)),
+        }
+    }
+
+    if let (Some(&validation_data_index), Some(&timestamp_index)) =
+        (validation_data.first(), timestamps.first())
+        && timestamp_index < validation_data_index
+    {
+        issues.push(InherentIssue::OutOfOrder {
+            validation_data_index,
+            timestamp_index,
+        });
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expected_layout_has_no_issues() {
+        assert!(find_issues(&[0], &[1]).is_empty());
+    }
+
+    #[test]
+    fn missing_inherents() {
+        assert_eq!(
+            find_issues(&[], &[]),
+            vec![
+                InherentIssue::Missing(SET_VALIDATION_DATA),
+                InherentIssue::Missing(TIMESTAMP_SET)
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicated_inherent() {
+        assert_eq!(
+            find_issues(&[0], &[1, 2]),
+            vec![InherentIssue::Duplicated(TIMESTAMP_SET, 2)]
+        );
+    }
+
+    #[test]
+    fn timestamp_before_validation_data() {
+        let issues = find_issues(&[3], &[1]);
+        assert_eq!(
+            issues,
+            vec![InherentIssue::OutOfOrder {
+                validation_data_index: 3,
+                timestamp_index: 1
+            }]
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            "Out of order: Timestamp.set (#1) before ParachainSystem.set_validation_data (#3)"
+        );
+    }
+}
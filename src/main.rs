@@ -1,18 +1,56 @@
-use clap::Parser as ClapParser;
-use codec::Encode;
-use subxt::{OnlineClient, PolkadotConfig};
-
-#[subxt::subxt(runtime_metadata_path = "./artifacts/asset_hub_kusama.scale")]
-pub mod asset_hub_kusama {}
-
-#[subxt::subxt(runtime_metadata_path = "./artifacts/kusama.scale")]
-pub mod kusama_relay_chain {}
+use std::path::PathBuf;
+use std::time::Duration;
+
+use archive::archive;
+use archive_relay::archive_relay;
+use check_config::check_config;
+use clap::{Args, Parser as ClapParser, Subcommand};
+use config::{Config, NetworkConfig, NetworkOverrides};
+use metadata::update_metadata;
+use monitor::AsyncBackingMonitor;
+use summary::SummaryOptions;
+
+mod archive;
+mod archive_relay;
+mod async_backing;
+mod author;
+mod backing;
+mod check_config;
+mod collations;
+mod config;
+mod coretime;
+mod disputes;
+mod duplicate;
+mod equivocation;
+mod health;
+mod inherents;
+mod metadata;
+mod monitor;
+mod on_demand;
+mod propagation;
+mod runtime;
+mod summary;
+mod timeline;
+mod timestamp;
+mod upgrade;
+mod window;
+
+/// Command line interface of the monitor.
+#[derive(Debug, ClapParser)]
+struct Cli {
+    /// Path to a TOML configuration file defining named networks.
+    ///
+    /// Networks from the file are merged with the built-in presets
+    /// (`kusama-asset-hub`, `polkadot-asset-hub`).
+    #[clap(long, global = true)]
+    config: Option<PathBuf>,
 
-type ParaInclusionEvent =
-    kusama_relay_chain::runtime_types::polkadot_runtime_parachains::inclusion::pallet::Event;
+    #[clap(subcommand)]
+    command: Command,
+}
 
 /// Command for interacting with the CLI.
-#[derive(Debug, ClapParser)]
+#[derive(Debug, Subcommand)]
 enum Command {
     /// Subscribe to the parachain and relay chain blocks.
     ///
@@ -20,11 +58,26 @@ enum Command {
     /// and will continuously monitor for new blocks, printing out the block number,
     /// hash, author, and timestamp of each block (and warn on duplicated timestamps).
     Subscribe {
-        #[clap(long, default_value = "wss://rpc-kusama.helixstreet.io")]
-        relay_chain_url: String,
+        #[clap(flatten)]
+        network: NetworkArgs,
+
+        /// Print the session summary every N minutes, in addition to shutdown.
+        #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+        summary_interval: Option<u64>,
 
-        #[clap(long, default_value = "wss://asset-hub-kusama.dotters.network")]
-        parachain_url: String,
+        /// Also write the latest session summary to this file.
+        #[clap(long)]
+        summary_file: Option<PathBuf>,
+
+        /// Additional parachain RPC endpoint to measure the block propagation against.
+        ///
+        /// Can be repeated, extends the `parachain_observer_urls` of the network.
+        #[clap(long = "observer-url")]
+        observer_urls: Vec<String>,
+
+        /// Directory the evidence of collator equivocations is written to.
+        #[clap(long, default_value = "evidence")]
+        evidence_dir: PathBuf,
     },
 
     /// Archive mode to fetch and print blocks from the parachain.
@@ -32,552 +85,201 @@ enum Command {
     /// This command connects to the specified parachain URL and retrieves
     /// blocks within a specified range (default is 200 blocks back from the latest).
     Archive {
+        #[clap(flatten)]
+        network: NetworkArgs,
+
         #[clap(long)]
-        chain: Option<String>,
+        blocks_diff: Option<u32>,
 
-        #[clap(long, default_value = "wss://rpc-kusama.helixstreet.io")]
-        relay_chain_url: String,
+        /// Directory the evidence of collator equivocations is written to.
+        #[clap(long, default_value = "evidence")]
+        evidence_dir: PathBuf,
+    },
 
-        #[clap(long, default_value = "wss://asset-hub-kusama.dotters.network")]
-        parachain_url: String,
+    /// Archive mode to fetch and print relay chain blocks.
+    ///
+    /// This command walks a range of relay chain blocks (default is 200 blocks back
+    /// from the latest) and reports the candidates of the parachain backed, included
+    /// and timed out in each of them, mapped to their parachain blocks.
+    ArchiveRelay {
+        #[clap(flatten)]
+        network: NetworkArgs,
 
         #[clap(long)]
         blocks_diff: Option<u32>,
     },
-}
-
-#[tokio::main]
-pub async fn main() {
-    let args = Command::parse();
 
-    match args {
-        Command::Subscribe {
-            relay_chain_url,
-            parachain_url,
-        } => {
-            // Reconnect on loop errors.
-            loop {
-                if let Err(err) = AsyncBackingMonitor::new()
-                    .run(relay_chain_url.as_str(), parachain_url.as_str())
-                    .await
-                {
-                    eprintln!("{err}");
-                    println!("ERROR: {err}");
-                }
-            }
-        }
-        Command::Archive {
-            relay_chain_url,
-            parachain_url,
-            blocks_diff,
-            chain,
-        } => {
-            let (relay_chain_url, parachain_url, chain_name) = if let Some(chain) = chain {
-                match chain.as_str() {
-                    "kusama-asset-hub" => (
-                        "wss://rpc-kusama.helixstreet.io",
-                        "wss://asset-hub-kusama.dotters.network",
-                        "AssetHubKusama",
-                    ),
-                    "polkadot-asset-hub" => (
-                        "wss://dot-rpc.stakeworld.io",
-                        "wss://polkadot-asset-hub-rpc.polkadot.io",
-                        "AssetHubPolkadot",
-                    ),
-                    chain => {
-                        panic!(
-                        "Unsupported chain: {chain}. Supported names are: kusama-asset-hub, polkadot-asset-hub.
-                        Please use `relay_chain_url` and `parachain_url` flags to specify custom URLs."
-                        );
-                    }
-                }
-            } else {
-                (
-                    relay_chain_url.as_str(),
-                    parachain_url.as_str(),
-                    "AssetHubKusama",
-                )
-            };
+    /// Preflight check of the async backing configuration.
+    ///
+    /// This command inspects the latest state of the parachain and relay chain runtimes,
+    /// without scanning blocks, and reports whether the parachain is ready for async backing.
+    /// Run it before and after runtime upgrades.
+    CheckConfig {
+        #[clap(flatten)]
+        network: NetworkArgs,
+    },
 
-            archive(
-                relay_chain_url,
-                parachain_url,
-                chain_name,
-                blocks_diff.unwrap_or(200),
-            )
-            .await
-            .expect("Failed to run archive mode");
-        }
-    }
+    /// Refresh the bundled metadata artifacts.
+    ///
+    /// This command downloads the metadata of the relay chain and the parachain
+    /// into the artifacts directory, and reports the calls, events, storage entries and
+    /// constants used by the monitor whose shape changed. Rebuild afterwards to regenerate
+    /// the runtime types.
+    UpdateMetadata {
+        #[clap(flatten)]
+        network: NetworkArgs,
+
+        /// Directory of the metadata artifacts.
+        #[clap(long, default_value = "artifacts")]
+        artifacts_dir: PathBuf,
+
+        /// Write the artifacts even if items decoded by the monitor changed shape.
+        #[clap(long)]
+        force: bool,
+    },
 }
 
-async fn archive(
-    relay_chain_url: &str,
-    parachain_url: &str,
-    chain_name: &str,
-    blocks_diff: u32,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let now = std::time::Instant::now();
-
-    let api = OnlineClient::<PolkadotConfig>::from_url(parachain_url).await?;
-    println!("Connection with parachain established.");
-
-    let rpc_client = subxt_rpcs::RpcClient::from_url(parachain_url).await?;
-    let legacy_methods: subxt_rpcs::LegacyRpcMethods<PolkadotConfig> =
-        subxt_rpcs::LegacyRpcMethods::new(rpc_client.clone());
-    let chain_head_client =
-        subxt_rpcs::ChainHeadRpcMethods::<PolkadotConfig>::new(rpc_client.clone());
-    println!("Connection with RPC client established.");
-
-    let relay_rpc_client = subxt_rpcs::RpcClient::from_url(relay_chain_url).await?;
-    let relay_chain_head_client =
-        subxt_rpcs::ChainHeadRpcMethods::<PolkadotConfig>::new(relay_rpc_client.clone());
-
-    let latest = api.blocks().at_latest().await?;
-    let number = latest.header().number;
-    println!(
-        "{chain_name}: Latest parachain block #{number}, hash={:?}",
-        latest.hash()
-    );
-
-    let mut target = number - blocks_diff;
-    let mut timestamps = std::collections::HashMap::new();
-    let mut duplicated_blocks = std::collections::HashMap::new();
-    let mut last_author = None;
-
-    let mut authoring_statistincs = std::collections::HashMap::new();
-    let mut authoring_in_row = std::collections::HashMap::new();
-    let mut num_produced = 1;
+/// Selects the network to monitor.
+#[derive(Debug, Args)]
+struct NetworkArgs {
+    /// Named network from the configuration file or the built-in presets.
+    ///
+    /// Defaults to the `default_network` of the configuration file, or `kusama-asset-hub`.
+    #[clap(long)]
+    chain: Option<String>,
 
-    let mut prev_timestamp = None;
-    let mut prev_parent = None;
+    /// Overrides the relay chain URL of the selected network.
+    #[clap(long)]
+    relay_chain_url: Option<String>,
 
-    let mut delta_values = Vec::with_capacity(blocks_diff as usize);
-    let mut num_jumps = Vec::with_capacity(blocks_diff as usize);
+    /// Overrides the parachain URL of the selected network.
+    #[clap(long)]
+    parachain_url: Option<String>,
 
-    while target != number {
-        let hash = legacy_methods
-            .chain_get_block_hash(Some(target.into()))
-            .await?
-            .unwrap();
-        let block = api.blocks().at(hash).await?;
-        let block_number = block.header().number;
+    /// Overrides the parachain ID of the selected network.
+    #[clap(long)]
+    para_id: Option<u32>,
+}
 
-        target += 1;
+impl NetworkArgs {
+    /// Resolve the selected network from the configuration, exiting on unknown networks.
+    fn resolve(self, config: &Config) -> NetworkConfig {
+        let (chain, overrides) = self.into_overrides();
+        let network = or_exit(
+            config.network(chain.as_deref(), overrides),
+            "Failed to select the network",
+        );
 
-        if block.header().digest.logs.is_empty() {
-            println!("  No logs in this block.");
-            return Ok(());
+        if let Some(fallback) = network.runtime.fallback() {
+            println!(
+                "[!] {:?} metadata is not compiled in, decoding with the {fallback:?} metadata. Rebuild with `--features {}` for exact decoding.\n",
+                network.runtime,
+                network.runtime.feature().unwrap_or_default()
+            );
         }
 
-        let author = &block.header().digest.logs[0];
-
-        let extrinsics = block
-            .extrinsics()
-            .await
-            .inspect_err(|err| println!("Failed to decode extrinsics: {:?}", err))?;
+        network
+    }
 
-        let mut timestamp = None;
-        let mut timestamp_human = Default::default();
+    /// Resolve the selected network, whether its metadata is compiled in or not.
+    fn resolve_without_metadata(self, config: &Config) -> NetworkConfig {
+        let (chain, overrides) = self.into_overrides();
+        or_exit(
+            config.network_without_metadata(chain.as_deref(), overrides),
+            "Failed to select the network",
+        )
+    }
 
-        let mut duplicate = None;
+    fn into_overrides(self) -> (Option<String>, NetworkOverrides) {
+        (
+            self.chain,
+            NetworkOverrides {
+                relay_chain_url: self.relay_chain_url,
+                parachain_url: self.parachain_url,
+                para_id: self.para_id,
+            },
+        )
+    }
+}
 
-        let validation_data = extrinsics.iter().next();
+/// Unwrap the result, or print the error and exit with a non-zero status.
+fn or_exit<T>(result: Result<T, Box<dyn std::error::Error>>, context: &str) -> T {
+    result.unwrap_or_else(|err| {
+        eprintln!("{context}: {err}");
+        std::process::exit(1)
+    })
+}
 
-        let mut relay_chain_parent = None;
-        if let Some(ext) = validation_data {
-            if let Ok(Some(validation_data)) =
-                ext.as_extrinsic::<asset_hub_kusama::parachain_system::calls::types::SetValidationData>()
-            {
-               relay_chain_parent = Some(validation_data.data.validation_data.relay_parent_number);
-            }
-        }
+#[tokio::main]
+pub async fn main() {
+    let args = Cli::parse();
+    let config = or_exit(
+        Config::load(args.config.as_deref()),
+        "Failed to load configuration",
+    );
 
-        let ext = extrinsics.iter().skip(1).next();
-        if let Some(ext) = ext {
-            let bytes = ext.bytes().to_vec();
-            timestamp = Some(bytes.clone());
-
-            if let Ok(Some(timestamp_ext)) =
-                ext.as_extrinsic::<asset_hub_kusama::timestamp::calls::types::Set>()
-            {
-                use chrono::TimeZone;
-                let timestamp_ms = timestamp_ext.now;
-
-                let seconds = (timestamp_ms / 1_000) as i64;
-                let nanos = ((timestamp_ms % 1_000) * 1_000_000) as u32;
-                timestamp_human = chrono::Utc.timestamp_opt(seconds, nanos).single().expect(
-                    "Failed to convert timestamp to human-readable format; this should not happen",
-                );
-            }
-
-            match timestamps.entry(bytes) {
-                std::collections::hash_map::Entry::Occupied(mut entry) => {
-                    let block = entry.get_mut();
-                    duplicated_blocks.insert(*block, block_number);
-                    duplicate = Some((*block, block_number));
-                }
-                std::collections::hash_map::Entry::Vacant(entry) => {
-                    entry.insert(block_number);
-                }
-            }
-        }
+    match args.command {
+        Command::Subscribe {
+            network,
+            summary_interval,
+            summary_file,
+            observer_urls,
+            evidence_dir,
+        } => {
+            let mut network = network.resolve(&config);
+            network.parachain_observer_urls.extend(observer_urls);
+            let summary = SummaryOptions {
+                interval: summary_interval.map(|minutes| Duration::from_secs(minutes * 60)),
+                file: summary_file,
+            };
 
-        let author_bytes = author.encode();
-        authoring_statistincs
-            .entry(author_bytes.clone())
-            .and_modify(|count| *count += 1)
-            .or_insert(1);
-
-        let same_author = last_author
-            .as_ref()
-            .map(|last| last == &author_bytes)
-            .unwrap_or(false);
-        if same_author {
-            num_produced += 1;
-        } else {
-            if num_produced > 1 {
-                authoring_in_row
-                    .entry(num_produced)
-                    .and_modify(|count| *count += 1)
-                    .or_insert(1);
-            }
-
-            num_produced = 1;
+            AsyncBackingMonitor::new(network, summary, evidence_dir)
+                .run_until_shutdown()
+                .await;
         }
+        Command::Archive {
+            network,
+            blocks_diff,
+            evidence_dir,
+        } => {
+            let network = network.resolve(&config);
 
-        let ident = (0..num_produced - 1).map(|_| "    ").collect::<String>();
-
-        let author_label = if same_author {
-            format!("Same (times: {})", num_produced)
-        } else {
-            "New".into()
-        };
-        last_author = Some(author_bytes);
-
-        if let Some((origin_block, _duplicate_number)) = duplicate {
-            println!(
-                "{ident}[X] {chain_name}: Block #{block_number}, hash={:?}",
-                block.hash(),
-            );
-            println!(
-                "{ident}  |--> {author_label} Author: {:?}",
-                hex::encode(author.encode())
-            );
-            println!(
-                "{ident}  |--> ({}) Duplicate Timestamp extrinsic found: initial={} current_block={} Timestamp.Set: 0x{} | {:?}\n",
-                duplicated_blocks.len(),
-                origin_block,
-                block_number,
-                hex::encode(timestamp.unwrap_or_default()),
-                timestamp_human,
-            );
-            println!("{ident}  |--> Relay Chain Parent: {:?}", relay_chain_parent);
-
-            // Check if the parachain contained a fork during that time.
-            let blocks = chain_head_client
-                .archive_v1_hash_by_height(origin_block as usize)
-                .await
-                .map_err(|err| {
-                    eprintln!("Failed to fetch archive hash for block {origin_block}: {err}");
-                    err
-                })?;
-            println!(
-                "{ident}  |--> Archive hash for block {origin_block}: {:?}",
-                blocks
-            );
-
-            let blocks = chain_head_client
-                .archive_v1_hash_by_height(origin_block as usize - 1)
-                .await
-                .map_err(|err| {
-                    eprintln!(
-                        "Failed to fetch archive hash for block {}: {err}",
-                        origin_block - 1
-                    );
-                    err
-                })?;
-            println!(
-                "{ident}  |--> Archive hash for block {}: {:?}",
-                origin_block - 1,
-                blocks
-            );
-
-            let parent = relay_chain_parent.expect("Relay chain parent should be present");
-            let relay_chain_block = relay_chain_head_client
-                .archive_v1_hash_by_height(parent as usize)
-                .await
-                .map_err(|err| {
-                    eprintln!("Failed to fetch relay chain archive hash for block {parent}: {err}");
-                    err
-                })?;
-            println!(
-                "{ident}  |--> Relay Chain Archive hash for block {parent}: {:?}\n",
-                relay_chain_block
-            );
-
-            if let (Some(prev_timestamp), Some(prev_parent)) = (prev_timestamp, prev_parent) {
-                delta_values.push(
-                    timestamp_human
-                        .signed_duration_since(prev_timestamp)
-                        .num_seconds(),
-                );
-                num_jumps.push(parent - prev_parent);
-
-                println!(
-                    "{ident}  |--> Elapsed {:?} seconds | jumped num={:?} relay chain blocks",
-                    timestamp_human
-                        .signed_duration_since(prev_timestamp)
-                        .num_seconds(),
-                    parent - prev_parent,
-                );
-            }
-        } else {
-            println!(
-                "{ident}{chain_name}: Block #{block_number}, hash={:?}",
-                block.hash(),
-            );
-            println!(
-                "{ident}  |--> {author_label} Author: {:?}",
-                hex::encode(author.encode())
-            );
-            println!(
-                "{ident}  |--> Timestamp.Set: 0x{} | {:?}",
-                hex::encode(timestamp.unwrap_or_default()),
-                timestamp_human,
-            );
-
-            let parent = relay_chain_parent.expect("Relay chain parent should be present");
-            println!("{ident}  |--> Relay Chain Parent: {:?}", parent);
-
-            let relay_chain_block = relay_chain_head_client
-                .archive_v1_hash_by_height(parent as usize)
-                .await
-                .map_err(|err| {
-                    eprintln!("Failed to fetch relay chain archive hash for block {parent}: {err}");
-                    err
-                })?;
-            println!(
-                "{ident}  |--> Relay Chain Archive hash for block {parent}: {:?}",
-                relay_chain_block
+            or_exit(
+                archive(&network, blocks_diff.unwrap_or(200), &evidence_dir).await,
+                "Failed to run archive mode",
             );
-
-            if let (Some(prev_timestamp), Some(prev_parent)) = (prev_timestamp, prev_parent) {
-                delta_values.push(
-                    timestamp_human
-                        .signed_duration_since(prev_timestamp)
-                        .num_seconds(),
-                );
-                num_jumps.push(parent - prev_parent);
-
-                println!(
-                    "{ident}  |--> Elapsed {:?} seconds | jumped num={:?} relay chain blocks",
-                    timestamp_human
-                        .signed_duration_since(prev_timestamp)
-                        .num_seconds(),
-                    parent - prev_parent,
-                );
-            }
-
-            println!();
         }
+        Command::ArchiveRelay {
+            network,
+            blocks_diff,
+        } => {
+            let network = network.resolve(&config);
 
-        prev_timestamp = Some(timestamp_human);
-        prev_parent = relay_chain_parent;
-    }
-
-    println!("Archive completed successfully.");
-    println!(" Average block time: {:.2} seconds", {
-        if !delta_values.is_empty() {
-            delta_values.iter().sum::<i64>() as f64 / delta_values.len() as f64
-        } else {
-            0.0
+            or_exit(
+                archive_relay(&network, blocks_diff.unwrap_or(200)).await,
+                "Failed to run relay archive mode",
+            );
         }
-    });
+        Command::CheckConfig { network } => {
+            let network = network.resolve(&config);
 
-    println!(
-        "Number of sequential blocks with the same timestamp: {} / {} ({:.2}%)",
-        duplicated_blocks.len(),
-        blocks_diff,
-        (duplicated_blocks.len() as f64 / blocks_diff as f64 * 100.0)
-    );
-    println!(" - produced in a row: {:#?}", authoring_in_row);
-
-    println!("Took {:?}", now.elapsed());
-
-    Ok(())
-}
-
-struct AsyncBackingMonitor {
-    timestamps: std::collections::HashMap<Vec<u8>, u32>,
-    relay_chain_time: std::time::Instant,
-    now: std::time::Instant,
-    duplicated_blocks: std::collections::HashMap<u32, u32>,
-    last_author: Option<Vec<u8>>,
-}
-
-impl AsyncBackingMonitor {
-    fn new() -> Self {
-        Self {
-            timestamps: std::collections::HashMap::new(),
-            relay_chain_time: std::time::Instant::now(),
-            now: std::time::Instant::now(),
-            duplicated_blocks: std::collections::HashMap::new(),
-            last_author: None,
+            or_exit(
+                check_config(&network).await,
+                "Failed to check the configuration",
+            );
         }
-    }
+        Command::UpdateMetadata {
+            network,
+            artifacts_dir,
+            force,
+        } => {
+            // The metadata is downloaded here, it does not need to be compiled in.
+            let network = network.resolve_without_metadata(&config);
 
-    async fn run(
-        mut self,
-        relay_chain_url: &str,
-        parachain_url: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let api = OnlineClient::<PolkadotConfig>::from_url(parachain_url).await?;
-        println!("Connection with parachain established.");
-
-        let kusama_api = OnlineClient::<PolkadotConfig>::from_url(relay_chain_url).await?;
-        println!("Connection with Kusama relay chain established.");
-
-        let mut parachain_sub = api.blocks().subscribe_best().await?;
-        let mut relay_chain_sub = kusama_api.blocks().subscribe_best().await?;
-
-        loop {
-            tokio::select! {
-                block = relay_chain_sub.next() => {
-                    let Some(block) = block else {
-                        break;
-                    };
-                    let block = block?;
-
-                    let block_number = block.header().number;
-                    println!(
-                        "  Relay Block #{block_number}, hash={:?} (elasped {:?})",
-                        block.hash(),
-                        self.relay_chain_time.elapsed()
-                    );
-                    self.relay_chain_time = std::time::Instant::now();
-
-                    // Log each of the extrinsic with it's associated events:
-                    let extrinsics = block.extrinsics().await?;
-                    for ext in extrinsics.iter() {
-                        let events = ext.events().await?;
-
-                        for evt in events.iter() {
-                            let evt = evt?;
-                            let Ok(event) = evt.as_root_event::<kusama_relay_chain::Event>() else {
-                                continue;
-                            };
-
-                            match event {
-                                kusama_relay_chain::Event::ParaInclusion(
-                                    ParaInclusionEvent::CandidateBacked(receipt, ..),
-                                ) => {
-                                    let descriptor = receipt.descriptor;
-                                    let para_id = descriptor.para_id.0;
-                                    let relay_chain_parent = descriptor.relay_parent;
-                                    let para_head = descriptor.para_head;
-
-                                    if para_id != 1000 {
-                                        continue;
-                                    }
-                                    println!(
-                                        "   |--> CandidateBacked: para_head={:?} relay_parent={:?}\n",
-                                        para_head, relay_chain_parent
-                                    );
-                                }
-                                _ => (),
-                            };
-                        }
-                    }
-                },
-
-                block = parachain_sub.next() => {
-                    let Some(block) = block else {
-                        break;
-                    };
-                    let block = block?;
-
-                    let block_number = block.header().number;
-
-                    if block.header().digest.logs.is_empty() {
-                        println!("  No logs in this block.");
-                        return Ok(());
-                    }
-
-                    let author = &block.header().digest.logs[0];
-
-                    let extrinsics = block
-                        .extrinsics()
-                        .await
-                        .inspect_err(|err| println!("Failed to decode extrinsics: {:?}", err))?;
-
-                    let mut timestamp = None;
-                    let mut duplicate = None;
-
-                    let ext = extrinsics.iter().skip(1).next();
-                    if let Some(ext) = ext {
-                        let bytes = ext.bytes().to_vec();
-                        timestamp = Some(bytes.clone());
-
-                        match self.timestamps.entry(bytes) {
-                            std::collections::hash_map::Entry::Occupied(mut entry) => {
-                                let block = entry.get_mut();
-                                self.duplicated_blocks.insert(*block, block_number);
-                                duplicate = Some((*block, block_number));
-                            }
-                            std::collections::hash_map::Entry::Vacant(entry) => {
-                                entry.insert(block_number);
-                            }
-                        }
-                    }
-
-                    let author_bytes = author.encode();
-                    let same_author = self
-                        .last_author
-                        .as_ref()
-                        .map(|last| last == &author_bytes)
-                        .unwrap_or(false);
-                    let author_labe = if same_author { "Same" } else { "New" };
-                    self.last_author = Some(author_bytes);
-
-                    if let Some((origin_block, _duplicate_number)) = duplicate {
-                        println!(
-                            "[X] AssetHubKusama: Block #{block_number}, hash={:?} (elasped {:?})",
-                            block.hash(),
-                            self.now.elapsed()
-                        );
-                        println!(
-                            "  |--> {author_labe} Author: {:?}",
-                            hex::encode(author.encode())
-                        );
-                        println!(
-                            "  |--> ({}) Duplicate Timestamp extrinsic found: initial={} current_block={} Timestamp.Set: 0x{}\n",
-                            self.duplicated_blocks.len(),
-                            origin_block,
-                            block_number,
-                            hex::encode(timestamp.unwrap_or_default())
-                        );
-                    } else {
-                        println!(
-                            "AssetHubKusama: Block #{block_number}, hash={:?} (elasped {:?})",
-                            block.hash(),
-                            self.now.elapsed()
-                        );
-                        println!(
-                            "  |--> {author_labe} Author: {:?}",
-                            hex::encode(author.encode())
-                        );
-                        println!(
-                            "  |--> Timestamp.Set: 0x{}\n",
-                            hex::encode(timestamp.unwrap_or_default())
-                        );
-                    }
-
-                    self.now = std::time::Instant::now();
-                }
-            }
+            or_exit(
+                update_metadata(&network, &artifacts_dir, force).await,
+                "Failed to update the metadata",
+            );
         }
-
-        Ok(())
     }
 }
@@ -0,0 +1,391 @@
+use std::path::Path;
+
+use codec::{Decode, Encode};
+use scale_info::{Field, PortableRegistry, TypeDef, Variant, form::PortableForm};
+use subxt::PolkadotConfig;
+
+use crate::config::NetworkConfig;
+use crate::runtime::artifact_spec_version;
+use crate::upgrade::format_spec;
+
+/// The metadata version of the bundled artifacts, as fetched by `subxt metadata`.
+const METADATA_VERSION: u32 = 15;
+
+/// Nesting depth after which types are no longer expanded, which also stops recursive types.
+const MAX_SHAPE_DEPTH: usize = 12;
+
+#[derive(Debug, Clone, Copy)]
+enum ItemKind {
+    Call,
+    Event,
+    Storage,
+    Constant,
+}
+
+/// A call, event, storage entry or constant decoded by the monitor.
+#[derive(Debug, Clone, Copy)]
+struct Item {
+    kind: ItemKind,
+    pallet: &'static str,
+    name: &'static str,
+}
+
+const fn call(pallet: &'static str, name: &'static str) -> Item {
+    Item {
+        kind: ItemKind::Call,
+        pallet,
+        name,
+    }
+}
+
+const fn event(pallet: &'static str, name: &'static str) -> Item {
+    Item {
+        kind: ItemKind::Event,
+        pallet,
+        name,
+    }
+}
+
+const fn storage(pallet: &'static str, name: &'static str) -> Item {
+    Item {
+        kind: ItemKind::Storage,
+        pallet,
+        name,
+    }
+}
+
+const fn constant(pallet: &'static str, name: &'static str) -> Item {
+    Item {
+        kind: ItemKind::Constant,
+        pallet,
+        name,
+    }
+}
+
+const RELAY_CHAIN_ITEMS: &[Item] = &[
+    call("ParaInherent", "enter"),
+    event("ParaInclusion", "CandidateBacked"),
+    event("ParaInclusion", "CandidateIncluded"),
+    event("ParaInclusion", "CandidateTimedOut"),
+    event("ParasDisputes", "DisputeInitiated"),
+    event("ParasDisputes", "DisputeConcluded"),
+    event("ParasDisputes", "Revert"),
+    event("OnDemandAssignmentProvider", "OnDemandOrderPlaced"),
+    event("OnDemandAssignmentProvider", "SpotPriceSet"),
+    event("Paras", "CodeUpgradeScheduled"),
+    event("Paras", "CurrentCodeUpdated"),
+    storage("Configuration", "ActiveConfig"),
+    storage("ParaScheduler", "ClaimQueue"),
+    storage("ParaScheduler", "ValidatorGroups"),
+    storage("ParasShared", "CurrentSessionIndex"),
+    storage("ParasShared", "ActiveValidatorIndices"),
+    storage("ParasShared", "ActiveValidatorKeys"),
+    storage("OnDemandAssignmentProvider", "FreeEntries"),
+    storage("OnDemandAssignmentProvider", "AffinityEntries"),
+    storage("OnDemandAssignmentProvider", "ParaIdAffinity"),
+    storage("Timestamp", "Now"),
+    storage("System", "LastRuntimeUpgrade"),
+];
+
+const PARACHAIN_ITEMS: &[Item] = &[
+    call("ParachainSystem", "set_validation_data"),
+    call("Timestamp", "set"),
+    event("ParachainSystem", "ValidationFunctionStored"),
+    event("ParachainSystem", "ValidationFunctionApplied"),
+    event("System", "CodeUpdated"),
+    storage("ParachainSystem", "UnincludedSegment"),
+    storage("ParachainSystem", "HostConfiguration"),
+    storage("AuraExt", "SlotInfo"),
+    storage("System", "LastRuntimeUpgrade"),
+    constant("Aura", "SlotDuration"),
+    constant("Timestamp", "MinimumPeriod"),
+];
+
+/// Download the metadata of both chains into the artifacts directory.
+///
+/// The calls, events, storage entries and constants decoded by the monitor are compared
+/// between the replaced and the downloaded metadata, as any change of their shape requires
+/// adapting the decoders. The artifacts are only written when no item changed, or with `force`.
+pub async fn update_metadata(
+    network: &NetworkConfig,
+    artifacts_dir: &Path,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (relay_file, parachain_file) = network.runtime.artifact_files();
+
+    let mut changed = 0;
+    let mut downloaded = Vec::new();
+    for (chain, url, file, items) in [
+        (
+            "Relay chain",
+            network.relay_chain_url.as_str(),
+            relay_file,
+            RELAY_CHAIN_ITEMS,
+        ),
+        (
+            network.display_name.as_str(),
+            network.parachain_url.as_str(),
+            parachain_file,
+            PARACHAIN_ITEMS,
+        ),
+    ] {
+        let path = artifacts_dir.join(file);
+        println!(
+            "{chain}: fetching metadata from {url} into {}",
+            path.display()
+        );
+
+        let new = fetch_metadata(url).await?;
+        let old = std::fs::read(&path).ok();
+        println!(
+            "  spec {} -> {}",
+            format_spec(old.as_deref().and_then(artifact_spec_version)),
+            format_spec(artifact_spec_version(&new))
+        );
+
+        changed += compare(old.as_deref(), &new, items)?;
+        downloaded.push((path, new));
+        println!();
+    }
+
+    if changed > 0 && !force {
+        println!(
+            "{changed} item(s) used by the monitor changed shape, the artifacts are not written: adapt the decoders in src/runtime.rs and rerun with --force."
+        );
+        return Ok(());
+    }
+
+    for (path, new) in downloaded {
+        std::fs::write(&path, &new)
+            .map_err(|err| format!("Failed to write {}: {err}", path.display()))?;
+    }
+
+    if changed == 0 {
+        println!("The items used by the monitor are unchanged.");
+    } else {
+        println!(
+            "{changed} item(s) used by the monitor changed shape: rebuild and adapt the decoders in src/runtime.rs."
+        );
+    }
+
+    Ok(())
+}
+
+/// Fetch the metadata of a chain, encoded like the artifacts of `subxt metadata -f bytes`.
+async fn fetch_metadata(url: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let rpc_client = subxt_rpcs::RpcClient::from_url(url).await?;
+    let legacy_methods: subxt_rpcs::LegacyRpcMethods<PolkadotConfig> =
+        subxt_rpcs::LegacyRpcMethods::new(rpc_client);
+
+    let response = legacy_methods
+        .state_call(
+            "Metadata_metadata_at_version",
+            Some(&METADATA_VERSION.encode()),
+            None,
+        )
+        .await?;
+    match Option::<Vec<u8>>::decode(&mut &response[..])? {
+        Some(metadata) => Ok(metadata),
+        // Runtimes without the versioned metadata API only serve the default version.
+        None => Ok(legacy_methods.state_get_metadata(None).await?.into_raw()),
+    }
+}
+
+/// Print the shape changes of `items` and return how many changed.
+fn compare(
+    old: Option<&[u8]>,
+    new: &[u8],
+    items: &[Item],
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let new = subxt::Metadata::decode(&mut &new[..])?;
+    let Some(old) = old else {
+        println!("  No previous artifact, nothing to compare.");
+        return Ok(0);
+    };
+    let old = subxt::Metadata::decode(&mut &old[..])?;
+
+    let mut changed = 0;
+    for item in items {
+        let kind = match item.kind {
+            ItemKind::Call => "call",
+            ItemKind::Event => "event",
+            ItemKind::Storage => "storage",
+            ItemKind::Constant => "constant",
+        };
+        let label = format!("{}.{} ({kind})", item.pallet, item.name);
+
+        match (shape(&old, item), shape(&new, item)) {
+            (Some(old), Some(new)) if old == new => println!("  [ok] {label}: unchanged"),
+            (Some(old), Some(new)) => {
+                changed += 1;
+                println!("  [!] {label}: changed");
+                println!("      old: {old}");
+                println!("      new: {new}");
+            }
+            (None, Some(_)) => println!("  [i] {label}: added"),
+            (Some(_), None) => {
+                changed += 1;
+                println!("  [!] {label}: removed");
+            }
+            (None, None) => println!("  [i] {label}: not present"),
+        }
+    }
+
+    Ok(changed)
+}
+
+/// The fields of a call or event, the key and value of a storage entry or the type of a
+/// constant, with their types fully expanded.
+fn shape(metadata: &subxt::Metadata, item: &Item) -> Option<String> {
+    let pallet = metadata.pallet_by_name(item.pallet)?;
+    let types = metadata.types();
+    let variants = match item.kind {
+        ItemKind::Call => pallet.call_variants()?,
+        ItemKind::Event => pallet.event_variants()?,
+        ItemKind::Storage => {
+            let entry = pallet.storage()?.entry_by_name(item.name)?.entry_type();
+            let value = type_shape(types, entry.value_ty(), 0);
+            return Some(match entry.key_ty() {
+                Some(key) => format!("{} -> {value}", type_shape(types, key, 0)),
+                None => value,
+            });
+        }
+        ItemKind::Constant => {
+            let constant = pallet.constant_by_name(item.name)?;
+            return Some(type_shape(types, constant.ty(), 0));
+        }
+    };
+    let variant = variants.iter().find(|variant| variant.name == item.name)?;
+
+    Some(variant_shape(types, variant, 0))
+}
+
+fn variant_shape(
+    types: &PortableRegistry,
+    variant: &Variant<PortableForm>,
+    depth: usize,
+) -> String {
+    format!(
+        "{}({})",
+        variant.name,
+        fields_shape(types, &variant.fields, depth)
+    )
+}
+
+fn fields_shape(types: &PortableRegistry, fields: &[Field<PortableForm>], depth: usize) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            let ty = type_shape(types, field.ty.id, depth + 1);
+            match &field.name {
+                Some(name) => format!("{name}: {ty}"),
+                None => ty,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Describe the layout of a type, ignoring its path and docs.
+fn type_shape(types: &PortableRegistry, id: u32, depth: usize) -> String {
+    if depth > MAX_SHAPE_DEPTH {
+        return "..".into();
+    }
+    let Some(ty) = types.resolve(id) else {
+        return format!("<unknown type {id}>");
+    };
+
+    match &ty.type_def {
+        TypeDef::Composite(composite) => {
+            format!("{{{}}}", fields_shape(types, &composite.fields, depth))
+        }
+        TypeDef::Variant(variant) => variant
+            .variants
+            .iter()
+            .map(|variant| variant_shape(types, variant, depth + 1))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        TypeDef::Sequence(sequence) => {
+            format!("[{}]", type_shape(types, sequence.type_param.id, depth + 1))
+        }
+        TypeDef::Array(array) => format!(
+            "[{}; {}]",
+            type_shape(types, array.type_param.id, depth + 1),
+            array.len
+        ),
+        TypeDef::Tuple(tuple) => {
+            let fields = tuple
+                .fields
+                .iter()
+                .map(|field| type_shape(types, field.id, depth + 1))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("({fields})")
+        }
+        TypeDef::Primitive(primitive) => format!("{primitive:?}").to_lowercase(),
+        TypeDef::Compact(compact) => format!(
+            "Compact<{}>",
+            type_shape(types, compact.type_param.id, depth + 1)
+        ),
+        TypeDef::BitSequence(_) => "BitSequence".into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KUSAMA: &[u8] = include_bytes!("../artifacts/kusama.scale");
+    const ASSET_HUB_KUSAMA: &[u8] = include_bytes!("../artifacts/asset_hub_kusama.scale");
+
+    /// Replace every occurrence of `from` by `to`, of the same length to keep the encoding valid.
+    fn replace(metadata: &[u8], from: &[u8], to: &[u8]) -> Vec<u8> {
+        assert_eq!(from.len(), to.len());
+
+        let mut metadata = metadata.to_vec();
+        let mut i = 0;
+        while i + from.len() <= metadata.len() {
+            if &metadata[i..i + from.len()] == from {
+                metadata[i..i + from.len()].copy_from_slice(to);
+                i += from.len();
+            } else {
+                i += 1;
+            }
+        }
+
+        metadata
+    }
+
+    #[test]
+    fn bundled_artifacts_have_every_item() {
+        for (artifact, items) in [
+            (KUSAMA, RELAY_CHAIN_ITEMS),
+            (ASSET_HUB_KUSAMA, PARACHAIN_ITEMS),
+        ] {
+            let metadata = subxt::Metadata::decode(&mut &artifact[..]).unwrap();
+            for item in items {
+                assert!(shape(&metadata, item).is_some(), "{item:?} not found");
+            }
+        }
+    }
+
+    #[test]
+    fn artifact_is_unchanged_against_itself() {
+        assert_eq!(compare(Some(KUSAMA), KUSAMA, RELAY_CHAIN_ITEMS).unwrap(), 0);
+        assert_eq!(
+            compare(Some(ASSET_HUB_KUSAMA), ASSET_HUB_KUSAMA, PARACHAIN_ITEMS).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn renamed_event_is_reported() {
+        let mutated = replace(KUSAMA, b"SpotPriceSet", b"SpotPriceGet");
+
+        assert_eq!(
+            compare(Some(KUSAMA), &mutated, RELAY_CHAIN_ITEMS).unwrap(),
+            1
+        );
+        assert_eq!(compare(None, &mutated, RELAY_CHAIN_ITEMS).unwrap(), 0);
+    }
+}
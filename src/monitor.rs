@@ -0,0 +1,1276 @@
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use codec::Encode;
+use subxt::{OnlineClient, PolkadotConfig, utils::H256};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::collations::{CollationIssue, CollationTracker};
+use crate::config::NetworkConfig;
+use crate::coretime::{self, CoreTracker, format_cores};
+use crate::disputes::{DisputeTracker, DisputeUpdate};
+use crate::equivocation::{EquivocationDetector, EvidenceBlock};
+use crate::health::{self, Chain, HealthReport};
+use crate::inherents::Inherents;
+use crate::on_demand::{Order, OrderTracker, OrderUpdate};
+use crate::propagation::{self, Import, PropagationTracker, Sightings};
+use crate::runtime::{
+    Block, DisputeEvent, HostConfiguration, InclusionEvent, OnDemandEvent, SessionGroups,
+};
+use crate::summary::{SessionStats, SummaryOptions, percent};
+use crate::timestamp::{self, RelayParentTime};
+use crate::upgrade::{SpecTracker, format_spec};
+use crate::window::{FirstSeenWindow, TimestampWindow};
+
+/// The clients of a connected chain.
+struct ChainClient {
+    api: OnlineClient<PolkadotConfig>,
+    legacy: subxt_rpcs::LegacyRpcMethods<PolkadotConfig>,
+}
+
+impl ChainClient {
+    async fn connect(url: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let rpc_client = subxt_rpcs::RpcClient::from_url(url).await?;
+        let api = OnlineClient::<PolkadotConfig>::from_rpc_client(rpc_client.clone()).await?;
+        let legacy = subxt_rpcs::LegacyRpcMethods::new(rpc_client);
+
+        Ok(Self { api, legacy })
+    }
+
+    /// Fetch the canonical block at the given height.
+    async fn block_at(&self, number: u32) -> Result<Block, Box<dyn std::error::Error>> {
+        let hash = self
+            .legacy
+            .chain_get_block_hash(Some(number.into()))
+            .await?
+            .ok_or_else(|| format!("Block #{number} not found"))?;
+
+        Ok(self.api.blocks().at(hash).await?)
+    }
+
+    /// Fetch the canonical block hash at the given height.
+    async fn hash_at(&self, number: u32) -> Result<H256, Box<dyn std::error::Error>> {
+        Ok(self
+            .legacy
+            .chain_get_block_hash(Some(number.into()))
+            .await?
+            .ok_or_else(|| format!("Block #{number} not found"))?)
+    }
+}
+
+/// The connection of the monitor that failed.
+#[derive(Debug, Clone, Copy)]
+enum Connection {
+    Relay,
+    Parachain,
+}
+
+impl Connection {
+    /// Attribute an error to this connection.
+    fn err<E: Into<Box<dyn std::error::Error>>>(self) -> impl FnOnce(E) -> ConnectionError {
+        move |err| ConnectionError {
+            connection: self,
+            error: err.into(),
+        }
+    }
+}
+
+/// An error that interrupted one of the connections.
+struct ConnectionError {
+    connection: Connection,
+    error: Box<dyn std::error::Error>,
+}
+
+/// A candidate of the parachain waiting for availability.
+struct PendingCandidate {
+    /// The relay chain block the candidate was backed in.
+    backed_at: u32,
+    session: u32,
+    group: u32,
+}
+
+/// Subscribes to the relay chain and parachain best blocks.
+///
+/// The state of the monitor is preserved across reconnects.
+pub struct AsyncBackingMonitor {
+    network: NetworkConfig,
+    timestamps: TimestampWindow,
+    relay_chain_time: Instant,
+    now: Instant,
+    last_author: Option<Vec<u8>>,
+
+    /// Index of the relay chain endpoint in use.
+    relay_endpoint: usize,
+    /// Index of the parachain endpoint in use.
+    parachain_endpoint: usize,
+    /// Number of consecutive failed connection attempts.
+    attempt: u32,
+    /// The moment the last connection was lost, until reconnected.
+    disconnected_at: Option<Instant>,
+
+    /// Highest relay chain block processed, used to detect gaps.
+    last_relay_number: Option<u32>,
+    /// Highest parachain block processed, used to detect gaps.
+    last_parachain_number: Option<u32>,
+    /// Timestamp of the previous parachain block.
+    last_timestamp: Option<u64>,
+
+    /// The candidate of the parachain occupying each core, until included or timed out.
+    pending_availability: BTreeMap<u32, PendingCandidate>,
+    /// The backing groups of the current session.
+    session_groups: Option<SessionGroups>,
+    /// Disputes raised against the parachain candidates.
+    disputes: DisputeTracker,
+    /// Parachain block numbers by hash, to link candidates to blocks.
+    parachain_hashes: FirstSeenWindow<H256>,
+    /// The relay chain host configuration of the current session.
+    host_configuration: Option<HostConfiguration>,
+    /// The cores assigned to the parachain.
+    cores: CoreTracker,
+    /// Client of the coretime chain, connected on first use.
+    coretime: Option<OnlineClient<PolkadotConfig>>,
+    /// On-demand orders waiting for a candidate of the parachain.
+    orders: OrderTracker,
+    /// Imported parachain blocks and backed candidates waiting for each other.
+    collations: CollationTracker,
+    /// Spec versions of the relay chain and parachain blocks.
+    relay_spec: SpecTracker,
+    parachain_spec: SpecTracker,
+    /// Parachain blocks by height and author, to detect collator equivocations.
+    equivocations: EquivocationDetector,
+    /// Directory the evidence of equivocations is written to.
+    evidence_dir: PathBuf,
+    /// Imports of the parachain blocks by the primary and the observer nodes.
+    propagation: PropagationTracker,
+    /// Parachain blocks imported by the primary and the observer nodes, once they are spawned.
+    imports: Option<UnboundedReceiver<Import>>,
+    /// Health reports of the endpoints, once the checks are spawned.
+    health_reports: Option<UnboundedReceiver<HealthReport>>,
+    /// The latest health reports of the relay chain and parachain endpoints.
+    relay_health: Option<HealthReport>,
+    parachain_health: Option<HealthReport>,
+
+    /// The previous parachain block was received live, hence the elapsed time is a block time.
+    last_block_live: bool,
+
+    /// Statistics of the session, reported on shutdown.
+    stats: SessionStats,
+    summary: SummaryOptions,
+    /// Ticks when the periodic summary is due.
+    summary_interval: Option<tokio::time::Interval>,
+}
+
+impl AsyncBackingMonitor {
+    pub fn new(network: NetworkConfig, summary: SummaryOptions, evidence_dir: PathBuf) -> Self {
+        let summary_interval = summary
+            .interval
+            .map(|period| tokio::time::interval_at(tokio::time::Instant::now() + period, period));
+        let (relay_bundled_spec, parachain_bundled_spec) = network.runtime.bundled_spec_versions();
+
+        Self {
+            timestamps: TimestampWindow::new(network.window),
+            stats: SessionStats::new(&network),
+            relay_chain_time: Instant::now(),
+            now: Instant::now(),
+            last_author: None,
+            relay_endpoint: 0,
+            parachain_endpoint: 0,
+            attempt: 0,
+            disconnected_at: None,
+            last_relay_number: None,
+            last_parachain_number: None,
+            last_timestamp: None,
+            pending_availability: BTreeMap::new(),
+            session_groups: None,
+            disputes: DisputeTracker::new(
+                network.window,
+                network.thresholds.dispute_timeout_blocks,
+            ),
+            parachain_hashes: FirstSeenWindow::new(network.window),
+            host_configuration: None,
+            cores: CoreTracker::default(),
+            coretime: None,
+            orders: OrderTracker::new(network.thresholds.on_demand_order_timeout_blocks),
+            collations: CollationTracker::new(
+                network.thresholds.collation_timeout_blocks,
+                network.window,
+            ),
+            relay_spec: SpecTracker::new(relay_bundled_spec),
+            parachain_spec: SpecTracker::new(parachain_bundled_spec),
+            equivocations: EquivocationDetector::new(network.window),
+            evidence_dir,
+            propagation: PropagationTracker::new(1 + network.parachain_observer_urls.len()),
+            imports: None,
+            health_reports: None,
+            relay_health: None,
+            parachain_health: None,
+            network,
+            last_block_live: false,
+            summary,
+            summary_interval,
+        }
+    }
+
+    /// Run the monitor until SIGINT or SIGTERM is received, then report the session summary.
+    ///
+    /// On errors, the failed connection switches to the next endpoint of the network
+    /// and reconnects with an exponential backoff.
+    pub async fn run_until_shutdown(mut self) {
+        let mut shutdown = std::pin::pin!(shutdown_signal());
+
+        // Observer nodes run independently of the primary connections and their reconnects.
+        // The primary parachain endpoint is observed as node 0, so every node is timed alike.
+        if self.propagation.is_enabled() {
+            let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+            let urls = std::iter::once(&self.network.parachain_url)
+                .chain(&self.network.parachain_observer_urls);
+            for (node, url) in urls.enumerate() {
+                tokio::spawn(propagation::observe(
+                    node,
+                    url.clone(),
+                    self.network.reconnect.clone(),
+                    sender.clone(),
+                ));
+            }
+            self.imports = Some(receiver);
+        }
+        if self.network.health.interval_secs > 0 {
+            let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+            tokio::spawn(health::watch(self.network.clone(), sender));
+            self.health_reports = Some(receiver);
+        }
+
+        loop {
+            let result = tokio::select! {
+                result = self.run() => result,
+                _ = &mut shutdown => break,
+            };
+            let Err(ConnectionError { connection, error }) = result;
+
+            let delay = self.network.reconnect.backoff(self.attempt);
+            self.attempt += 1;
+            self.disconnected_at.get_or_insert_with(Instant::now);
+
+            match connection {
+                Connection::Relay => self.relay_endpoint += 1,
+                Connection::Parachain => self.parachain_endpoint += 1,
+            }
+
+            println!(
+                "[!] {connection:?} connection lost: {error}. Reconnecting in {delay:?} (attempt {})\n",
+                self.attempt
+            );
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {},
+                _ = &mut shutdown => break,
+            }
+        }
+
+        println!("\n[!] Shutting down.\n");
+        self.stats.report(&self.summary);
+    }
+
+    async fn run(&mut self) -> Result<Infallible, ConnectionError> {
+        let parachain_urls = self.network.parachain_urls();
+        let parachain_url = parachain_urls[self.parachain_endpoint % parachain_urls.len()];
+        let relay_chain_urls = self.network.relay_chain_urls();
+        let relay_chain_url = relay_chain_urls[self.relay_endpoint % relay_chain_urls.len()];
+
+        let parachain = ChainClient::connect(parachain_url)
+            .await
+            .map_err(Connection::Parachain.err())?;
+        println!("Connection with parachain established ({parachain_url}).");
+
+        let relay_chain = ChainClient::connect(relay_chain_url)
+            .await
+            .map_err(Connection::Relay.err())?;
+        println!("Connection with relay chain established ({relay_chain_url}).");
+
+        let mut parachain_sub = parachain
+            .api
+            .blocks()
+            .subscribe_best()
+            .await
+            .map_err(Connection::Parachain.err())?;
+        let mut relay_chain_sub = relay_chain
+            .api
+            .blocks()
+            .subscribe_best()
+            .await
+            .map_err(Connection::Relay.err())?;
+
+        if let Some(disconnected_at) = self.disconnected_at.take() {
+            let outage = disconnected_at.elapsed();
+            self.stats.outages += 1;
+            self.stats.outage_duration += outage;
+            self.last_block_live = false;
+
+            println!(
+                "[!] Outage: reconnected after {outage:?} (relay={relay_chain_url}, parachain={parachain_url}) | total outages: {} ({:?})\n",
+                self.stats.outages, self.stats.outage_duration
+            );
+        }
+        self.attempt = 0;
+
+        loop {
+            tokio::select! {
+                block = relay_chain_sub.next() => {
+                    let block = block
+                        .ok_or("Relay chain subscription closed")
+                        .map_err(Connection::Relay.err())?
+                        .map_err(Connection::Relay.err())?;
+
+                    self.backfill_relay_chain(&relay_chain, block.header().number)
+                        .await
+                        .map_err(Connection::Relay.err())?;
+                    self.process_relay_block(block, &relay_chain, false)
+                        .await
+                        .map_err(Connection::Relay.err())?;
+                },
+
+                block = parachain_sub.next() => {
+                    let block = block
+                        .ok_or("Parachain subscription closed")
+                        .map_err(Connection::Parachain.err())?
+                        .map_err(Connection::Parachain.err())?;
+
+                    self.backfill_parachain(&parachain, &relay_chain, block.header().number)
+                        .await?;
+                    self.process_parachain_block(block, &relay_chain, false)
+                        .await?;
+                }
+
+                import = recv(&mut self.imports) => {
+                    if let Some(sightings) = self.propagation.record_import(import) {
+                        self.report_propagation(&sightings);
+                    }
+                    if import.node == 0 {
+                        for sightings in self.propagation.expire(import.number) {
+                            self.report_propagation(&sightings);
+                        }
+                    }
+                }
+
+                report = recv(&mut self.health_reports) => {
+                    self.process_health_report(report);
+                }
+
+                _ = tick(&mut self.summary_interval) => {
+                    self.stats.report(&self.summary);
+                }
+            }
+        }
+    }
+
+    /// Process the relay chain blocks missed before `number`.
+    async fn backfill_relay_chain(
+        &mut self,
+        client: &ChainClient,
+        number: u32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let missing = missing_blocks(
+            "Relay chain",
+            self.last_relay_number,
+            number,
+            self.network.reconnect.max_backfill_blocks,
+        );
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        println!(
+            "[!] Relay chain: backfilling blocks #{}..#{}\n",
+            missing.start,
+            missing.end - 1
+        );
+        for number in missing {
+            let block = client.block_at(number).await?;
+            self.process_relay_block(block, client, true).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Process the parachain blocks missed before `number`.
+    async fn backfill_parachain(
+        &mut self,
+        client: &ChainClient,
+        relay_chain: &ChainClient,
+        number: u32,
+    ) -> Result<(), ConnectionError> {
+        let missing = missing_blocks(
+            &self.network.display_name,
+            self.last_parachain_number,
+            number,
+            self.network.reconnect.max_backfill_blocks,
+        );
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        println!(
+            "[!] {}: backfilling blocks #{}..#{}\n",
+            self.network.display_name,
+            missing.start,
+            missing.end - 1
+        );
+        for number in missing {
+            let block = client
+                .block_at(number)
+                .await
+                .map_err(Connection::Parachain.err())?;
+            self.process_parachain_block(block, relay_chain, true)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn process_relay_block(
+        &mut self,
+        block: Block,
+        relay_chain: &ChainClient,
+        backfilled: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let block_number = block.header().number;
+        self.last_relay_number = self.last_relay_number.max(Some(block_number));
+        self.stats.relay_blocks += 1;
+
+        if backfilled {
+            println!(
+                "  Relay Block #{block_number}, hash={:?} (backfilled)",
+                block.hash()
+            );
+        } else {
+            println!(
+                "  Relay Block #{block_number}, hash={:?} (elasped {:?})",
+                block.hash(),
+                self.relay_chain_time.elapsed()
+            );
+            self.relay_chain_time = Instant::now();
+        }
+
+        let spec_version = self.network.runtime.relay_spec_version(&block).await?;
+        let first = self.relay_spec.current().is_none();
+        let change = self.relay_spec.update(spec_version);
+        if let Some(change) = change {
+            self.stats
+                .runtime_upgrades
+                .push(format!("Relay chain #{block_number}: {change}"));
+            println!("   |--> [!!!] Relay chain runtime upgrade: {change}");
+        }
+        if (first || change.is_some())
+            && let Some(warning) = self.relay_spec.bundled_mismatch()
+        {
+            println!("   |--> [!] Relay chain runtime: {warning}");
+        }
+
+        let extrinsics = block.extrinsics().await?;
+        let inherent = extrinsics
+            .iter()
+            .find_map(|ext| {
+                self.network
+                    .runtime
+                    .para_inherent(&ext, self.network.para_id)
+            })
+            .unwrap_or_default();
+
+        // Group indices are only meaningful within the session they were assigned in.
+        let session = self
+            .network
+            .runtime
+            .session_index(&relay_chain.api, block.hash())
+            .await?;
+        let session_groups = match self.session_groups.take() {
+            Some(groups) if groups.session == session => groups,
+            _ => {
+                // The host configuration only changes at session boundaries.
+                let configuration = self
+                    .network
+                    .runtime
+                    .host_configuration(&relay_chain.api, block.hash())
+                    .await?;
+                if let Some(configuration) = configuration
+                    && self.host_configuration != Some(configuration)
+                {
+                    println!("   |--> Host configuration: {configuration}");
+                    self.stats.async_backing.set_configuration(configuration);
+                }
+                self.host_configuration = configuration;
+
+                self.network
+                    .runtime
+                    .session_groups(&relay_chain.api, block.hash())
+                    .await?
+            }
+        };
+
+        let claims = self
+            .network
+            .runtime
+            .para_claims(&relay_chain.api, block.hash(), self.network.para_id)
+            .await?;
+        let has_core = !claims.cores.is_empty();
+        let pool_cores = self.cores.update_pool(claims.pool_cores);
+        if let Some(change) = self.cores.update(claims.cores) {
+            println!("   |--> [!] Coretime: {change}");
+            self.report_broker_cores().await;
+        }
+        let mut backed = 0;
+
+        // Availability of the candidates backed in previous blocks.
+        for (core_index, pending) in &self.pending_availability {
+            let (available, total) = inherent.availability(*core_index);
+            println!(
+                "   |--> Availability: core={core_index} {available}/{total} bitfields ({:.2}%) | pending for {} relay blocks",
+                percent(available as u64, total as u64),
+                block_number.saturating_sub(pending.backed_at)
+            );
+        }
+        for dispute in &inherent.disputes {
+            println!(
+                "   |--> [!] Dispute statements: candidate={:?} session={} valid={} invalid={}",
+                dispute.candidate_hash, dispute.session, dispute.valid, dispute.invalid
+            );
+        }
+
+        // Log each of the extrinsic with it's associated events:
+        for ext in extrinsics.iter() {
+            let events = ext.events().await?;
+
+            for evt in events.iter() {
+                let evt = evt?;
+                if let Some(event) = self.network.runtime.dispute_event(&evt) {
+                    self.process_dispute_event(block_number, &event);
+                    continue;
+                }
+                if let Some(event) = self.network.runtime.on_demand_event(&evt) {
+                    self.process_on_demand_event(block_number, event);
+                    continue;
+                }
+                if let Some(upgrade) = self
+                    .network
+                    .runtime
+                    .relay_upgrade_event(&evt, self.network.para_id)
+                {
+                    println!("   |--> [!] Runtime upgrade: {upgrade}\n");
+                    continue;
+                }
+
+                let Some(event) = self.network.runtime.inclusion_event(&evt) else {
+                    continue;
+                };
+
+                if event.para_id() != self.network.para_id {
+                    continue;
+                }
+
+                match event {
+                    InclusionEvent::CandidateBacked {
+                        candidate_hash,
+                        para_head,
+                        relay_parent,
+                        core_index,
+                        group_index,
+                        ..
+                    } => {
+                        self.stats.candidates_backed += 1;
+                        backed += 1;
+                        // Only candidates backed on an on-demand claim consume an order.
+                        if pool_cores.contains(&core_index)
+                            && let Some(update) = self.orders.on_backed(block_number)
+                        {
+                            self.report_order(update);
+                        }
+                        self.disputes
+                            .record_candidate(block_number, candidate_hash, para_head);
+                        self.pending_availability.insert(
+                            core_index,
+                            PendingCandidate {
+                                backed_at: block_number,
+                                session,
+                                group: group_index,
+                            },
+                        );
+                        println!(
+                            "   |--> CandidateBacked: para_head={:?} relay_parent={:?} core={core_index} group={group_index}",
+                            para_head, relay_parent
+                        );
+                        match self.collations.on_backed(para_head, block_number) {
+                            Some(number) => {
+                                println!("   |--> {} block #{number}", self.network.display_name)
+                            }
+                            None => println!(
+                                "   |--> [!] {} block not imported by the parachain RPC node",
+                                self.network.display_name
+                            ),
+                        }
+
+                        let relay_parent_number = relay_chain
+                            .api
+                            .blocks()
+                            .at(relay_parent)
+                            .await?
+                            .header()
+                            .number;
+                        let age = block_number.saturating_sub(relay_parent_number);
+                        println!("   |--> Relay parent age: {age} relay blocks");
+                        if let Some(issue) = self.stats.async_backing.record_relay_parent_age(age) {
+                            println!("   |--> [!] Async backing: {issue}");
+                        }
+
+                        let candidate = inherent.backed_candidate(para_head);
+                        self.stats.backing.record_backed(
+                            &session_groups,
+                            group_index,
+                            candidate.map(|candidate| candidate.validator_indices.as_slice()),
+                        );
+                        if let Some(candidate) = candidate {
+                            println!(
+                                "   |--> Validity votes: {}/{} (minimum {})",
+                                candidate.validity_votes,
+                                session_groups.members(group_index).len(),
+                                self.host_configuration
+                                    .map(|configuration| configuration
+                                        .minimum_backing_votes
+                                        .to_string())
+                                    .unwrap_or_else(|| "unknown".into())
+                            );
+                            if self.host_configuration.is_some_and(|configuration| {
+                                candidate.validity_votes
+                                    <= configuration.minimum_backing_votes as usize
+                            }) {
+                                self.stats.candidates_at_minimum_votes += 1;
+                                println!("   |--> [!] Backed with the minimum number of votes");
+                            }
+                        }
+                        println!();
+                    }
+                    InclusionEvent::CandidateIncluded {
+                        para_head,
+                        relay_parent,
+                        core_index,
+                        group_index,
+                        ..
+                    } => {
+                        self.stats.candidates_included += 1;
+                        println!(
+                            "   |--> CandidateIncluded: para_head={:?} relay_parent={:?} core={core_index} group={group_index}",
+                            para_head, relay_parent
+                        );
+                        let pending = self.pending_availability.remove(&core_index);
+                        let waited = pending
+                            .as_ref()
+                            .map(|pending| block_number.saturating_sub(pending.backed_at));
+                        self.stats.backing.record_included(
+                            pending.map_or(session, |pending| pending.session),
+                            group_index,
+                            waited,
+                        );
+                        if let Some(waited) = waited {
+                            self.stats.record_availability_wait(waited);
+                            println!("   |--> Available after {waited} relay blocks");
+                        }
+                        println!();
+                    }
+                    InclusionEvent::CandidateTimedOut {
+                        para_head,
+                        relay_parent,
+                        core_index,
+                        ..
+                    } => {
+                        self.stats.candidates_timed_out += 1;
+                        if let Some(pending) = self.pending_availability.remove(&core_index) {
+                            let members = if pending.session == session {
+                                session_groups.members(pending.group)
+                            } else {
+                                &[]
+                            };
+                            self.stats.backing.record_timed_out(
+                                pending.session,
+                                pending.group,
+                                members,
+                            );
+                        }
+                        println!(
+                            "   |--> CandidateTimedOut: para_head={:?} relay_parent={:?} core={core_index}\n",
+                            para_head, relay_parent
+                        );
+                    }
+                };
+            }
+        }
+
+        self.session_groups = Some(session_groups);
+
+        // Tell the blocks the parachain could not use from the blocks it did not use.
+        if backed == 0 {
+            if has_core {
+                self.stats.relay_blocks_idle_core += 1;
+            } else {
+                self.stats.relay_blocks_without_core += 1;
+            }
+        }
+
+        for update in self.disputes.expire(block_number) {
+            self.report_dispute(update);
+        }
+
+        for update in self.orders.expire(block_number) {
+            self.report_order(update);
+        }
+        for issue in self.collations.expire(block_number) {
+            match issue {
+                CollationIssue::NotImported { .. } => self.stats.collations_not_imported += 1,
+                CollationIssue::NotBacked { .. } => self.stats.collations_not_backed += 1,
+            }
+            println!("   |--> [!] Collation: {issue}\n");
+        }
+        if self.orders.pending() > 0 {
+            let queued = self
+                .network
+                .runtime
+                .on_demand_queued(&relay_chain.api, block.hash(), self.network.para_id)
+                .await?;
+            println!(
+                "   |--> On-demand: {} orders waiting for a candidate, {queued} in the queue\n",
+                self.orders.pending()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Print the cores the coretime chain assigns to the parachain, when configured.
+    async fn report_broker_cores(&mut self) {
+        let Some(url) = &self.network.coretime_url else {
+            return;
+        };
+
+        if self.coretime.is_none() {
+            match OnlineClient::<PolkadotConfig>::from_url(url).await {
+                Ok(api) => self.coretime = Some(api),
+                Err(err) => {
+                    println!("   |--> [!] Coretime chain connection failed ({url}): {err}");
+                    return;
+                }
+            }
+        }
+        let Some(api) = &self.coretime else {
+            return;
+        };
+
+        match coretime::broker_cores(api, self.network.para_id).await {
+            Ok(cores) => println!("   |--> Broker workload: cores {}", format_cores(&cores)),
+            Err(err) => {
+                println!("   |--> [!] Failed to fetch the broker workload: {err}");
+                self.coretime = None;
+            }
+        }
+    }
+
+    fn process_on_demand_event(&mut self, block_number: u32, event: OnDemandEvent) {
+        match event {
+            OnDemandEvent::OrderPlaced {
+                para_id,
+                spot_price,
+                ordered_by,
+            } if para_id == self.network.para_id => {
+                let order = Order {
+                    placed_at: block_number,
+                    spot_price,
+                    ordered_by,
+                };
+                self.stats.on_demand.record_order(&order);
+                self.orders.place(order);
+                println!(
+                    "   |--> On-demand order placed: spot_price={spot_price} ordered_by=0x{}\n",
+                    hex::encode(ordered_by)
+                );
+            }
+            OnDemandEvent::OrderPlaced { .. } => {}
+            OnDemandEvent::SpotPriceSet { spot_price } => {
+                self.stats.on_demand.spot_price = Some(spot_price);
+            }
+        }
+    }
+
+    fn report_order(&mut self, update: OrderUpdate) {
+        self.stats.on_demand.record(&update);
+
+        match update {
+            OrderUpdate::Backed { .. } => println!("   |--> On-demand: {update}"),
+            OrderUpdate::Wasted(_) => println!("   |--> [!] On-demand: {update}\n"),
+        }
+    }
+
+    fn process_dispute_event(&mut self, block_number: u32, event: &DisputeEvent) {
+        if let DisputeEvent::Revert { block } = event {
+            self.stats.disputes.reverts += 1;
+            println!(
+                "[!!!] Relay chain reverted to block #{block} after a dispute concluded against a candidate\n"
+            );
+            return;
+        }
+
+        if let Some(update) = self.disputes.on_event(block_number, event) {
+            self.report_dispute(update);
+        }
+    }
+
+    /// Print the issues of a health check and keep the report to explain slow blocks.
+    fn process_health_report(&mut self, report: HealthReport) {
+        self.stats.health.record(&report);
+
+        let (chain, urls, endpoint) = match report.chain {
+            Chain::Relay => (
+                "Relay chain",
+                self.network.relay_chain_urls(),
+                self.relay_endpoint,
+            ),
+            Chain::Parachain => (
+                self.network.display_name.as_str(),
+                self.network.parachain_urls(),
+                self.parachain_endpoint,
+            ),
+        };
+        let url = urls[endpoint % urls.len()];
+        for issue in &report.issues {
+            let in_use = if issue.url() == Some(url) {
+                " (endpoint in use)"
+            } else {
+                ""
+            };
+            println!("[!] {chain} health: {issue}{in_use}\n");
+        }
+
+        match report.chain {
+            Chain::Relay => self.relay_health = Some(report),
+            Chain::Parachain => self.parachain_health = Some(report),
+        }
+    }
+
+    /// Explain a slow parachain block with the latest health reports, if they tell.
+    fn slow_block_cause(&self) -> Option<&'static str> {
+        let urls = self.network.parachain_urls();
+        let url = urls[self.parachain_endpoint % urls.len()];
+        if let Some(report) = &self.parachain_health {
+            if report.endpoint_unhealthy(url) {
+                return Some("our RPC node is slow");
+            }
+            if report.chain_slow() {
+                return Some("the chain is slow");
+            }
+        }
+        if let Some(report) = &self.relay_health
+            && report.chain_slow()
+        {
+            return Some("the relay chain is slow");
+        }
+
+        None
+    }
+
+    /// Print the import lag of every node for a parachain block and record it in the session statistics.
+    fn report_propagation(&mut self, sightings: &Sightings) {
+        self.stats.propagation.record(sightings);
+
+        println!(
+            "[Propagation] {}: Block #{}, hash={:?} | {}\n",
+            self.network.display_name,
+            sightings.number,
+            sightings.hash,
+            sightings.render(self.stats.propagation.nodes())
+        );
+    }
+
+    /// Print a dispute of a parachain candidate and record it in the session statistics.
+    fn report_dispute(&mut self, update: DisputeUpdate) {
+        self.stats.disputes.record(&update);
+
+        let (dispute, status) = match update {
+            DisputeUpdate::Initiated(dispute) if dispute.local => {
+                (dispute, "initiated".to_string())
+            }
+            DisputeUpdate::Initiated(dispute) => (dispute, "imported".to_string()),
+            DisputeUpdate::Concluded(dispute, outcome) => (dispute, format!("concluded {outcome}")),
+        };
+        let parachain_block = self
+            .parachain_hashes
+            .get(&dispute.candidate.para_head)
+            .map(|number| format!("#{number}"))
+            .unwrap_or_else(|| "not seen".into());
+
+        println!(
+            "[!!!] Dispute {status}: candidate={:?} | {} block {parachain_block}, hash={:?} | backed at relay #{} | initiated at relay #{}\n",
+            dispute.candidate_hash,
+            self.network.display_name,
+            dispute.candidate.para_head,
+            dispute.candidate.backed_at,
+            dispute.initiated_at
+        );
+    }
+
+    /// Fetch the timestamp and the BABE slot of the relay chain block at the given height.
+    async fn relay_parent_time(
+        &self,
+        relay_chain: &ChainClient,
+        number: u32,
+    ) -> Result<RelayParentTime, Box<dyn std::error::Error>> {
+        let hash = relay_chain.hash_at(number).await?;
+        let header = relay_chain.legacy.chain_get_header(Some(hash)).await?;
+
+        Ok(RelayParentTime {
+            timestamp_ms: self
+                .network
+                .runtime
+                .relay_timestamp(&relay_chain.api, hash)
+                .await?,
+            slot_ms: header.and_then(|header| timestamp::babe_slot_ms(&header.digest)),
+        })
+    }
+
+    /// Process a parachain block received by the primary node.
+    async fn process_parachain_block(
+        &mut self,
+        block: Block,
+        relay_chain: &ChainClient,
+        backfilled: bool,
+    ) -> Result<(), ConnectionError> {
+        let block_number = block.header().number;
+        self.last_parachain_number = self.last_parachain_number.max(Some(block_number));
+        self.parachain_hashes.insert(block_number, block.hash());
+        let backed_at =
+            self.collations
+                .on_imported(block.hash(), block_number, self.last_relay_number);
+        if backed_at.is_some() {
+            self.stats.collations_imported_late += 1;
+        }
+
+        let Some(author) = self.network.author.extract(&block.header().digest) else {
+            println!(
+                "  No {} pre-runtime digest in block #{block_number}, skipped.\n",
+                self.network.author.engine()
+            );
+            return Ok(());
+        };
+
+        let equivocation = self.equivocations.record(
+            block_number,
+            &author,
+            EvidenceBlock {
+                hash: block.hash(),
+                header: block.header().encode(),
+            },
+        );
+        if equivocation.is_some() {
+            self.stats.equivocations += 1;
+        }
+
+        let extrinsics = block
+            .extrinsics()
+            .await
+            .inspect_err(|err| println!("Failed to decode extrinsics: {:?}", err))
+            .map_err(Connection::Parachain.err())?;
+
+        let inherents = Inherents::locate(&self.network.runtime, &extrinsics);
+        let relay_parent_number = inherents.relay_parent_number;
+        let timestamp = inherents.timestamp_bytes;
+        let timestamp_ms = inherents.timestamp;
+
+        let spec_version = self
+            .network
+            .runtime
+            .parachain_spec_version(&block)
+            .await
+            .map_err(Connection::Parachain.err())?;
+        let first_spec = self.parachain_spec.current().is_none();
+        let spec_change = self.parachain_spec.update(spec_version);
+        if let Some(change) = spec_change {
+            self.stats.runtime_upgrades.push(format!(
+                "{} #{block_number}: {change}",
+                self.network.display_name
+            ));
+        }
+        let spec_warning = (first_spec || spec_change.is_some())
+            .then(|| self.parachain_spec.bundled_mismatch())
+            .flatten();
+        let mut upgrade_events = Vec::new();
+        let events = block.events().await.map_err(Connection::Parachain.err())?;
+        for evt in events.iter() {
+            let evt = evt.map_err(Connection::Parachain.err())?;
+            if let Some(upgrade) = self.network.runtime.parachain_upgrade_event(&evt) {
+                upgrade_events.push(upgrade);
+            }
+        }
+
+        let unincluded_segment = self
+            .network
+            .runtime
+            .unincluded_segment_len(&block)
+            .await
+            .map_err(Connection::Parachain.err())?;
+        let async_backing_issues = self
+            .stats
+            .async_backing
+            .record_unincluded_segment(block_number, unincluded_segment);
+
+        let duplicate = timestamp_ms.and_then(|timestamp_ms| {
+            self.timestamps
+                .insert(block_number, timestamp_ms)
+                .map(|origin_block| (origin_block, block_number))
+        });
+
+        let relay_parent_time = match relay_parent_number {
+            Some(number) => self
+                .relay_parent_time(relay_chain, number)
+                .await
+                .map_err(Connection::Relay.err())?,
+            None => RelayParentTime::default(),
+        };
+        let limits = self.network.timestamp_limits(
+            self.host_configuration
+                .map(|configuration| configuration.allowed_ancestry_len),
+        );
+        let anomalies = timestamp_ms
+            .map(|timestamp_ms| {
+                timestamp::check(timestamp_ms, self.last_timestamp, relay_parent_time, limits)
+            })
+            .unwrap_or_default();
+        if timestamp_ms.is_some() {
+            self.last_timestamp = timestamp_ms;
+        }
+
+        let same_author = self
+            .last_author
+            .as_ref()
+            .map(|last| last == &author.id)
+            .unwrap_or(false);
+        let author_labe = if same_author { "Same" } else { "New" };
+        self.last_author = Some(author.id.clone());
+
+        self.stats.parachain_blocks += 1;
+        self.stats.record_author(same_author);
+        if duplicate.is_some() {
+            self.stats.duplicated_timestamps += 1;
+        }
+        for anomaly in &anomalies {
+            self.stats.record_timestamp_anomaly(anomaly);
+        }
+        if !inherents.issues.is_empty() {
+            self.stats.inherent_issues += 1;
+        }
+
+        let timestamp_human = timestamp_ms
+            .map(|timestamp_ms| timestamp::to_datetime(timestamp_ms).to_string())
+            .unwrap_or_else(|| "undecoded".into());
+
+        let arrival = if backfilled {
+            "backfilled".to_string()
+        } else {
+            format!("elasped {:?}", self.now.elapsed())
+        };
+
+        if let Some((origin_block, _duplicate_number)) = duplicate {
+            println!(
+                "[X] {}: Block #{block_number}, hash={:?}, spec={} ({arrival})",
+                self.network.display_name,
+                block.hash(),
+                format_spec(spec_version),
+            );
+            println!("  |--> {author_labe} Author: {author}");
+            println!(
+                "  |--> ({}) Duplicate Timestamp extrinsic found: initial={} current_block={} Timestamp.Set: 0x{} | {timestamp_human}",
+                self.stats.duplicated_timestamps,
+                origin_block,
+                block_number,
+                hex::encode(timestamp.unwrap_or_default())
+            );
+        } else {
+            println!(
+                "{}: Block #{block_number}, hash={:?}, spec={} ({arrival})",
+                self.network.display_name,
+                block.hash(),
+                format_spec(spec_version),
+            );
+            println!("  |--> {author_labe} Author: {author}");
+            println!(
+                "  |--> Timestamp.Set: 0x{} | {timestamp_human}",
+                hex::encode(timestamp.unwrap_or_default())
+            );
+        }
+
+        let drift =
+            timestamp_ms.and_then(|timestamp_ms| relay_parent_time.render_drift(timestamp_ms));
+        match (relay_parent_number, drift) {
+            (Some(number), Some(drift)) => {
+                println!("  |--> Relay Chain Parent: #{number} | {drift}")
+            }
+            (Some(number), None) => println!("  |--> Relay Chain Parent: #{number}"),
+            (None, _) => println!("  |--> Relay Chain Parent: missing"),
+        }
+        println!("  |--> Unincluded segment: {unincluded_segment} blocks");
+        if let Some(backed_at) = backed_at {
+            println!("  |--> [!] Imported after its candidate was backed at relay #{backed_at}");
+        }
+        if let Some(change) = spec_change {
+            println!("  |--> [!!!] Runtime upgrade: {change}");
+        }
+        if let Some(warning) = &spec_warning {
+            println!("  |--> [!] Runtime: {warning}");
+        }
+        for upgrade in &upgrade_events {
+            println!("  |--> [!] Runtime upgrade: {upgrade}");
+        }
+        for anomaly in &anomalies {
+            println!("  |--> [!] {anomaly}");
+        }
+        for issue in &inherents.issues {
+            println!("  |--> [!] Inherents: {issue}");
+        }
+        for issue in &async_backing_issues {
+            println!("  |--> [!] Async backing: {issue}");
+        }
+        if let Some(equivocation) = &equivocation {
+            println!("  |--> [!!!] Equivocation: {equivocation}");
+            match equivocation.export(&self.network, &self.evidence_dir) {
+                Ok(path) => println!("  |--> Evidence written to {}", path.display()),
+                Err(err) => println!("  |--> [!] Failed to write the evidence: {err}"),
+            }
+        }
+        println!();
+
+        if backfilled {
+            self.last_block_live = false;
+            return Ok(());
+        }
+
+        // The import times are recorded by the observer tasks, node 0 being the primary node.
+        if let Some(timestamp_ms) = timestamp_ms
+            && let Some(sightings) =
+                self.propagation
+                    .record_timestamp(block.hash(), block_number, timestamp_ms)
+        {
+            self.report_propagation(&sightings);
+        }
+
+        let elapsed = self.now.elapsed();
+        if elapsed.as_millis() > self.network.slow_block_ms() as u128 {
+            let cause = self
+                .slow_block_cause()
+                .map(|cause| format!(", {cause}"))
+                .unwrap_or_default();
+            println!(
+                "  |--> WARNING: Slow block, elapsed {:?} (threshold {}ms){cause}\n",
+                elapsed,
+                self.network.slow_block_ms()
+            );
+        }
+
+        if self.last_block_live {
+            self.stats
+                .record_block_time(block_number, elapsed.as_millis() as u64);
+        }
+        self.last_block_live = true;
+        self.now = Instant::now();
+
+        Ok(())
+    }
+}
+
+/// Completes when the periodic summary is due, never if it is disabled.
+/// The heights missed between the last processed block and `number`.
+///
+/// At most `max` of the most recent heights are returned.
+fn missing_blocks(chain: &str, last: Option<u32>, number: u32, max: u32) -> Range<u32> {
+    let Some(last) = last else {
+        return number..number;
+    };
+
+    let start = last + 1;
+    if start >= number {
+        return number..number;
+    }
+
+    if number - start > max {
+        println!(
+            "[!] {chain}: {} blocks missed, only the last {max} are backfilled\n",
+            number - start
+        );
+        return number - max..number;
+    }
+
+    start..number
+}
+
+async fn tick(interval: &mut Option<tokio::time::Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Completes with the next message of a background task, never if there is none.
+async fn recv<T>(receiver: &mut Option<UnboundedReceiver<T>>) -> T {
+    match receiver {
+        Some(receiver) => match receiver.recv().await {
+            Some(message) => message,
+            None => std::future::pending().await,
+        },
+        None => std::future::pending().await,
+    }
+}
+
+/// Completes on SIGINT or SIGTERM.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let mut terminate =
+            signal(SignalKind::terminate()).expect("Failed to install the SIGTERM handler");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {},
+            _ = terminate.recv() => {},
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nothing_is_missing_without_a_gap() {
+        assert!(missing_blocks("Chain", None, 100, 600).is_empty());
+        assert!(missing_blocks("Chain", Some(99), 100, 600).is_empty());
+    }
+
+    #[test]
+    fn gap_below_the_cap_is_backfilled() {
+        assert_eq!(missing_blocks("Chain", Some(90), 100, 600), 91..100);
+        assert_eq!(missing_blocks("Chain", Some(90), 100, 9), 91..100);
+    }
+
+    #[test]
+    fn gap_above_the_cap_keeps_the_latest_blocks() {
+        assert_eq!(missing_blocks("Chain", Some(90), 100, 5), 95..100);
+        assert_eq!(missing_blocks("Chain", Some(0), 1000, 600), 400..1000);
+    }
+
+    #[test]
+    fn head_behind_the_last_block_is_not_a_gap() {
+        assert!(missing_blocks("Chain", Some(100), 100, 600).is_empty());
+        assert!(missing_blocks("Chain", Some(120), 100, 600).is_empty());
+    }
+}
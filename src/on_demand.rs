@@ -0,0 +1,212 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+/// An on-demand order placed for the parachain.
+#[derive(Debug, Clone, Copy)]
+pub struct Order {
+    /// The relay chain block the order was placed in.
+    pub placed_at: u32,
+    pub spot_price: u128,
+    pub ordered_by: [u8; 32],
+}
+
+/// What became of an on-demand order.
+#[derive(Debug, Clone, Copy)]
+pub enum OrderUpdate {
+    /// A candidate of the parachain was backed `latency` relay chain blocks after the order.
+    Backed { order: Order, latency: u32 },
+    /// No candidate was backed within `on_demand_order_timeout_blocks`.
+    Wasted(Order),
+}
+
+impl fmt::Display for OrderUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderUpdate::Backed { order, latency } => write!(
+                f,
+                "order of relay block #{} (spot price {}) backed after {latency} relay blocks",
+                order.placed_at, order.spot_price
+            ),
+            OrderUpdate::Wasted(order) => write!(
+                f,
+                "order of relay block #{} (spot price {}, ordered by 0x{}) wasted, no candidate backed",
+                order.placed_at,
+                order.spot_price,
+                hex::encode(order.ordered_by)
+            ),
+        }
+    }
+}
+
+/// Matches the on-demand orders of the parachain with the candidates backed afterwards.
+///
+/// Orders are matched in placement order with the next candidates backed on a core
+/// assigned to the parachain through the on-demand pool.
+pub struct OrderTracker {
+    pending: VecDeque<Order>,
+    timeout_blocks: u32,
+}
+
+impl OrderTracker {
+    pub fn new(timeout_blocks: u32) -> Self {
+        Self {
+            pending: VecDeque::new(),
+            timeout_blocks,
+        }
+    }
+
+    /// Number of orders waiting for a backed candidate.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn place(&mut self, order: Order) {
+        self.pending.push_back(order);
+    }
+
+    /// Match an on-demand candidate backed at relay chain block `number` with the oldest
+    /// pending order.
+    pub fn on_backed(&mut self, number: u32) -> Option<OrderUpdate> {
+        let order = self.pending.pop_front()?;
+
+        Some(OrderUpdate::Backed {
+            order,
+            latency: number.saturating_sub(order.placed_at),
+        })
+    }
+
+    /// Give up on the orders still pending at relay chain block `number`.
+    pub fn expire(&mut self, number: u32) -> Vec<OrderUpdate> {
+        let mut wasted = Vec::new();
+        while let Some(order) = self.pending.front()
+            && number.saturating_sub(order.placed_at) >= self.timeout_blocks
+        {
+            wasted.extend(self.pending.pop_front().map(OrderUpdate::Wasted));
+        }
+
+        wasted
+    }
+}
+
+/// On-demand orders of the parachain over a session.
+#[derive(Debug, Default)]
+pub struct OnDemandStats {
+    pub orders: u64,
+    pub backed: u64,
+    pub wasted: u64,
+    /// Sum of the spot prices paid.
+    pub spent: u128,
+    /// Sum of the spot prices paid for wasted orders.
+    pub wasted_spent: u128,
+    /// The last spot price reported by `SpotPriceSet`.
+    pub spot_price: Option<u128>,
+    latency_total: u64,
+    latency_max: u32,
+}
+
+impl OnDemandStats {
+    pub fn record_order(&mut self, order: &Order) {
+        self.orders += 1;
+        self.spent += order.spot_price;
+    }
+
+    pub fn record(&mut self, update: &OrderUpdate) {
+        match update {
+            OrderUpdate::Backed { latency, .. } => {
+                self.backed += 1;
+                self.latency_total += *latency as u64;
+                self.latency_max = self.latency_max.max(*latency);
+            }
+            OrderUpdate::Wasted(order) => {
+                self.wasted += 1;
+                self.wasted_spent += order.spot_price;
+            }
+        }
+    }
+
+    pub fn render(&self) -> Vec<String> {
+        if self.orders == 0 {
+            return Vec::new();
+        }
+
+        vec![format!(
+            " On-demand orders: {} (spent {}) | backed {} | wasted {} (spent {}) | order to backing: avg {:.2} | max {} relay blocks | spot price {}",
+            self.orders,
+            self.spent,
+            self.backed,
+            self.wasted,
+            self.wasted_spent,
+            self.latency_total as f64 / self.backed.max(1) as f64,
+            self.latency_max,
+            self.spot_price
+                .map(|spot_price| spot_price.to_string())
+                .unwrap_or_else(|| "unknown".into())
+        )]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(placed_at: u32, spot_price: u128) -> Order {
+        Order {
+            placed_at,
+            spot_price,
+            ordered_by: [0; 32],
+        }
+    }
+
+    #[test]
+    fn orders_are_matched_in_placement_order() {
+        let mut tracker = OrderTracker::new(20);
+        tracker.place(order(10, 100));
+        tracker.place(order(12, 200));
+
+        match tracker.on_backed(15) {
+            Some(OrderUpdate::Backed { order, latency }) => {
+                assert_eq!(order.placed_at, 10);
+                assert_eq!(latency, 5);
+            }
+            update => panic!("unexpected update {update:?}"),
+        }
+        assert_eq!(tracker.pending(), 1);
+    }
+
+    #[test]
+    fn backing_without_an_order() {
+        assert!(OrderTracker::new(20).on_backed(15).is_none());
+    }
+
+    #[test]
+    fn orders_expire_after_the_timeout() {
+        let mut tracker = OrderTracker::new(20);
+        tracker.place(order(10, 100));
+        tracker.place(order(15, 200));
+
+        assert!(tracker.expire(29).is_empty());
+        let wasted = tracker.expire(30);
+        assert!(matches!(
+            wasted.as_slice(),
+            [OrderUpdate::Wasted(Order { placed_at: 10, .. })]
+        ));
+        assert_eq!(tracker.pending(), 1);
+    }
+
+    #[test]
+    fn stats_of_backed_and_wasted_orders() {
+        let mut stats = OnDemandStats::default();
+        for placed in [order(10, 100), order(12, 200)] {
+            stats.record_order(&placed);
+        }
+        stats.record(&OrderUpdate::Backed {
+            order: order(10, 100),
+            latency: 4,
+        });
+        stats.record(&OrderUpdate::Wasted(order(12, 200)));
+
+        assert_eq!((stats.orders, stats.backed, stats.wasted), (2, 1, 1));
+        assert_eq!((stats.spent, stats.wasted_spent), (300, 200));
+        assert!(OnDemandStats::default().render().is_empty());
+    }
+}
@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use subxt::utils::H256;
+use subxt::{OnlineClient, PolkadotConfig};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::async_backing::Samples;
+use crate::config::ReconnectConfig;
+
+/// Parachain blocks are reported once this many newer blocks were imported by the primary node,
+/// even if some nodes did not import them or their `Timestamp.set` is unknown.
+const PROPAGATION_WAIT_BLOCKS: u32 = 3;
+
+/// A parachain block imported by one of the RPC nodes.
+#[derive(Debug, Clone, Copy)]
+pub struct Import {
+    /// Index of the node, 0 being the primary parachain endpoint.
+    pub node: usize,
+    pub hash: H256,
+    pub number: u32,
+    /// Wall-clock time of the import, in milliseconds since the Unix epoch.
+    pub at_ms: u64,
+}
+
+/// The wall-clock time, in milliseconds since the Unix epoch.
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Forward the best blocks of a node, reconnecting with a backoff on errors.
+///
+/// The primary parachain endpoint is observed as node 0, like the observer nodes.
+///
+/// Returns once the monitor stops receiving imports.
+pub async fn observe(
+    node: usize,
+    url: String,
+    reconnect: ReconnectConfig,
+    imports: UnboundedSender<Import>,
+) {
+    let mut attempt = 0;
+    loop {
+        let delay = match observe_once(node, &url, &imports).await {
+            Ok(()) => return,
+            Err(err) => {
+                let delay = reconnect.backoff(attempt);
+                println!(
+                    "[!] Observer {url}: connection lost: {err}. Reconnecting in {delay:?} (attempt {})\n",
+                    attempt + 1
+                );
+                delay
+            }
+        };
+        attempt += 1;
+        tokio::time::sleep(delay).await;
+    }
+}
+
+async fn observe_once(
+    node: usize,
+    url: &str,
+    imports: &UnboundedSender<Import>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let api = OnlineClient::<PolkadotConfig>::from_url(url).await?;
+    let mut blocks = api.blocks().subscribe_best().await?;
+    println!("Connection with parachain observer established ({url}).");
+
+    while let Some(block) = blocks.next().await {
+        let block = block?;
+        let import = Import {
+            node,
+            hash: block.hash(),
+            number: block.header().number,
+            at_ms: now_ms(),
+        };
+        if imports.send(import).is_err() {
+            return Ok(());
+        }
+    }
+
+    Err("Observer subscription closed".into())
+}
+
+/// The imports of a parachain block by every node.
+#[derive(Debug, Clone)]
+pub struct Sightings {
+    pub number: u32,
+    pub hash: H256,
+    /// The `Timestamp.set` of the block, known once processed from the primary node.
+    pub timestamp_ms: Option<u64>,
+    pub imported_at: Vec<Option<u64>>,
+}
+
+impl Sightings {
+    /// The time of the first import among all nodes.
+    fn first_ms(&self) -> Option<u64> {
+        self.imported_at.iter().flatten().min().copied()
+    }
+
+    /// Describe the lag of every node, relative to the first import and to `Timestamp.set`.
+    pub fn render(&self, nodes: &[String]) -> String {
+        let first_ms = self.first_ms().unwrap_or_default();
+        nodes
+            .iter()
+            .zip(&self.imported_at)
+            .map(
+                |(name, imported_at)| match (imported_at, self.timestamp_ms) {
+                    (Some(at_ms), Some(timestamp_ms)) => format!(
+                        "{name} +{}ms (Timestamp.set +{}ms)",
+                        at_ms - first_ms,
+                        at_ms.saturating_sub(timestamp_ms)
+                    ),
+                    (Some(at_ms), None) => format!("{name} +{}ms", at_ms - first_ms),
+                    (None, _) => format!("{name} missed"),
+                },
+            )
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+/// Collects the imports of the parachain blocks by the primary and the observer nodes.
+pub struct PropagationTracker {
+    nodes: usize,
+    blocks: HashMap<H256, Sightings>,
+}
+
+impl PropagationTracker {
+    pub fn new(nodes: usize) -> Self {
+        Self {
+            nodes,
+            blocks: HashMap::new(),
+        }
+    }
+
+    /// Whether observer nodes are configured.
+    pub fn is_enabled(&self) -> bool {
+        self.nodes > 1
+    }
+
+    /// Record an import, returning the sightings of the block once complete.
+    pub fn record_import(&mut self, import: Import) -> Option<Sightings> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let sightings = self.sightings(import.hash, import.number);
+        sightings.imported_at[import.node].get_or_insert(import.at_ms);
+
+        self.take_complete(import.hash)
+    }
+
+    /// Record the `Timestamp.set` of a block processed from the primary node,
+    /// returning the sightings of the block once complete.
+    pub fn record_timestamp(
+        &mut self,
+        hash: H256,
+        number: u32,
+        timestamp_ms: u64,
+    ) -> Option<Sightings> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        self.sightings(hash, number).timestamp_ms = Some(timestamp_ms);
+
+        self.take_complete(hash)
+    }
+
+    /// Remove the sightings of a block once every node imported it and its timestamp is known.
+    fn take_complete(&mut self, hash: H256) -> Option<Sightings> {
+        let sightings = self.blocks.get(&hash)?;
+        if sightings.timestamp_ms.is_none() || sightings.imported_at.iter().any(Option::is_none) {
+            return None;
+        }
+
+        self.blocks.remove(&hash)
+    }
+
+    fn sightings(&mut self, hash: H256, number: u32) -> &mut Sightings {
+        let nodes = self.nodes;
+        self.blocks.entry(hash).or_insert_with(|| Sightings {
+            number,
+            hash,
+            timestamp_ms: None,
+            imported_at: vec![None; nodes],
+        })
+    }
+
+    /// Give up on the blocks some nodes did not import, once block `number` is imported.
+    pub fn expire(&mut self, number: u32) -> Vec<Sightings> {
+        let mut expired = Vec::new();
+        self.blocks.retain(|_, sightings| {
+            if number.saturating_sub(sightings.number) < PROPAGATION_WAIT_BLOCKS {
+                return true;
+            }
+            expired.push(sightings.clone());
+            false
+        });
+        expired.sort_by_key(|sightings| sightings.number);
+
+        expired
+    }
+}
+
+/// Import lag of a node over a session.
+#[derive(Debug, Default)]
+struct NodeStats {
+    /// Milliseconds after the first node that imported the block.
+    lag: Samples,
+    /// Milliseconds after the `Timestamp.set` of the block.
+    since_timestamp: Samples,
+    /// Blocks this node imported first.
+    first: u64,
+    /// Blocks imported by another node but not by this one.
+    missed: u64,
+}
+
+/// Block propagation across the parachain RPC nodes over a session.
+#[derive(Debug)]
+pub struct PropagationStats {
+    nodes: Vec<String>,
+    stats: Vec<NodeStats>,
+}
+
+impl PropagationStats {
+    pub fn new(nodes: Vec<String>) -> Self {
+        Self {
+            stats: nodes.iter().map(|_| NodeStats::default()).collect(),
+            nodes,
+        }
+    }
+
+    /// The names of the nodes, the primary parachain endpoint first.
+    pub fn nodes(&self) -> &[String] {
+        &self.nodes
+    }
+
+    pub fn record(&mut self, sightings: &Sightings) {
+        let Some(first_ms) = sightings.first_ms() else {
+            return;
+        };
+
+        for (stats, imported_at) in self.stats.iter_mut().zip(&sightings.imported_at) {
+            let Some(at_ms) = imported_at else {
+                stats.missed += 1;
+                continue;
+            };
+
+            stats.lag.record((at_ms - first_ms) as u32);
+            if *at_ms == first_ms {
+                stats.first += 1;
+            }
+            if let Some(timestamp_ms) = sightings.timestamp_ms {
+                stats
+                    .since_timestamp
+                    .record(at_ms.saturating_sub(timestamp_ms) as u32);
+            }
+        }
+    }
+
+    pub fn render(&self) -> Vec<String> {
+        if self.nodes.len() < 2 {
+            return Vec::new();
+        }
+
+        let mut lines = vec![" Block propagation:".to_string()];
+        for (name, stats) in self.nodes.iter().zip(&self.stats) {
+            lines.push(format!(
+                "   {name}: first {} | lag avg {:.0}ms | max {}ms | after Timestamp.set avg {:.0}ms | max {}ms | missed {}",
+                stats.first,
+                stats.lag.average(),
+                stats.lag.max,
+                stats.since_timestamp.average(),
+                stats.since_timestamp.max,
+                stats.missed
+            ));
+        }
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import(node: usize, number: u32, at_ms: u64) -> Import {
+        Import {
+            node,
+            hash: H256::repeat_byte(number as u8),
+            number,
+            at_ms,
+        }
+    }
+
+    fn nodes() -> Vec<String> {
+        vec!["primary".into(), "a".into(), "b".into()]
+    }
+
+    #[test]
+    fn sightings_complete_once_every_node_imported_the_block() {
+        let mut tracker = PropagationTracker::new(3);
+
+        assert!(tracker.record_import(import(1, 10, 1_000)).is_none());
+        assert!(tracker.record_import(import(0, 10, 1_040)).is_none());
+        assert!(
+            tracker
+                .record_timestamp(H256::repeat_byte(10), 10, 600)
+                .is_none()
+        );
+        let sightings = tracker.record_import(import(2, 10, 1_250)).unwrap();
+
+        assert_eq!(
+            sightings.imported_at,
+            vec![Some(1_040), Some(1_000), Some(1_250)]
+        );
+        assert_eq!(
+            sightings.render(&nodes()),
+            "primary +40ms (Timestamp.set +440ms) | a +0ms (Timestamp.set +400ms) | b +250ms (Timestamp.set +650ms)"
+        );
+        assert!(tracker.expire(20).is_empty());
+    }
+
+    #[test]
+    fn timestamp_after_every_import_completes_the_sightings() {
+        let mut tracker = PropagationTracker::new(2);
+        tracker.record_import(import(0, 10, 1_000));
+        tracker.record_import(import(1, 10, 1_100));
+
+        let sightings = tracker
+            .record_timestamp(H256::repeat_byte(10), 10, 900)
+            .unwrap();
+        assert_eq!(sightings.timestamp_ms, Some(900));
+    }
+
+    #[test]
+    fn first_import_per_node_is_kept() {
+        let mut tracker = PropagationTracker::new(2);
+        tracker.record_import(import(1, 10, 1_000));
+        tracker.record_import(import(1, 10, 2_000));
+        tracker.record_timestamp(H256::repeat_byte(10), 10, 900);
+
+        let sightings = tracker.record_import(import(0, 10, 1_500)).unwrap();
+        assert_eq!(sightings.imported_at, vec![Some(1_500), Some(1_000)]);
+    }
+
+    #[test]
+    fn nodes_that_never_import_the_block_expire() {
+        let mut tracker = PropagationTracker::new(3);
+        tracker.record_import(import(0, 10, 1_000));
+        tracker.record_import(import(2, 10, 1_300));
+        tracker.record_import(import(0, 11, 7_000));
+
+        assert!(tracker.expire(12).is_empty());
+        let expired = tracker.expire(13);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].number, 10);
+        assert_eq!(
+            expired[0].render(&nodes()),
+            "primary +0ms | a missed | b +300ms"
+        );
+
+        let mut stats = PropagationStats::new(nodes());
+        stats.record(&expired[0]);
+        assert_eq!(stats.stats[1].missed, 1);
+        assert_eq!(stats.stats[0].first, 1);
+        assert_eq!(stats.stats[2].lag.max, 300);
+    }
+
+    #[test]
+    fn single_node_is_not_tracked() {
+        let mut tracker = PropagationTracker::new(1);
+
+        assert!(tracker.record_import(import(0, 10, 1_000)).is_none());
+        assert!(
+            tracker
+                .record_timestamp(H256::repeat_byte(10), 10, 900)
+                .is_none()
+        );
+        assert!(tracker.expire(100).is_empty());
+    }
+}
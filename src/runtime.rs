@@ -0,0 +1,948 @@
+//! Generated runtime types and the per-network selection of decoders.
+//!
+//! Kusama metadata is always compiled in. Metadata for the other networks is
+//! large, therefore it is gated behind the `polkadot`, `paseo` and `westend` features.
+//! Without the `polkadot` feature, Polkadot is decoded with the Kusama metadata, like
+//! before the networks had their own decoders.
+//! The artifacts are fetched with `scripts/fetch-metadata.sh`.
+
+use codec::{Decode, Encode};
+use serde::Deserialize;
+use subxt::{OnlineClient, PolkadotConfig, utils::H256};
+
+#[subxt::subxt(runtime_metadata_path = "./artifacts/asset_hub_kusama.scale")]
+pub mod asset_hub_kusama {}
+
+#[subxt::subxt(runtime_metadata_path = "./artifacts/kusama.scale")]
+pub mod kusama_relay_chain {}
+
+#[cfg(feature = "polkadot")]
+#[subxt::subxt(runtime_metadata_path = "./artifacts/asset_hub_polkadot.scale")]
+pub mod asset_hub_polkadot {}
+
+#[cfg(feature = "polkadot")]
+#[subxt::subxt(runtime_metadata_path = "./artifacts/polkadot.scale")]
+pub mod polkadot_relay_chain {}
+
+#[cfg(feature = "paseo")]
+#[subxt::subxt(runtime_metadata_path = "./artifacts/asset_hub_paseo.scale")]
+pub mod asset_hub_paseo {}
+
+#[cfg(feature = "paseo")]
+#[subxt::subxt(runtime_metadata_path = "./artifacts/paseo.scale")]
+pub mod paseo_relay_chain {}
+
+#[cfg(feature = "westend")]
+#[subxt::subxt(runtime_metadata_path = "./artifacts/asset_hub_westend.scale")]
+pub mod asset_hub_westend {}
+
+#[cfg(feature = "westend")]
+#[subxt::subxt(runtime_metadata_path = "./artifacts/westend.scale")]
+pub mod westend_relay_chain {}
+
+/// Duration of a relay chain slot.
+pub const RELAY_CHAIN_SLOT_DURATION_MS: u64 = 6000;
+
+pub type Block = subxt::blocks::Block<PolkadotConfig, OnlineClient<PolkadotConfig>>;
+pub type Extrinsics = subxt::blocks::Extrinsics<PolkadotConfig, OnlineClient<PolkadotConfig>>;
+pub type ExtrinsicDetails =
+    subxt::blocks::ExtrinsicDetails<PolkadotConfig, OnlineClient<PolkadotConfig>>;
+pub type EventDetails = subxt::events::EventDetails<PolkadotConfig>;
+
+/// The runtime metadata used to decode the blocks of a network.
+///
+/// The parachain side is decoded with the Asset Hub metadata of the same ecosystem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Runtime {
+    Kusama,
+    Polkadot,
+    Paseo,
+    Westend,
+}
+
+/// Expands `$body` with `$relay` and `$para` aliasing the generated modules of `$runtime`.
+///
+/// `$unavailable` is returned when the metadata of `$runtime` is not compiled in.
+macro_rules! with_runtime {
+    ($runtime:expr, $unavailable:expr, |$relay:ident, $para:ident| $body:expr) => {
+        match $runtime {
+            $crate::runtime::Runtime::Kusama => {
+                #[allow(unused_imports)]
+                use $crate::runtime::asset_hub_kusama as $para;
+                #[allow(unused_imports)]
+                use $crate::runtime::kusama_relay_chain as $relay;
+                $body
+            }
+            #[cfg(feature = "polkadot")]
+            $crate::runtime::Runtime::Polkadot => {
+                #[allow(unused_imports)]
+                use $crate::runtime::asset_hub_polkadot as $para;
+                #[allow(unused_imports)]
+                use $crate::runtime::polkadot_relay_chain as $relay;
+                $body
+            }
+            #[cfg(not(feature = "polkadot"))]
+            $crate::runtime::Runtime::Polkadot => {
+                #[allow(unused_imports)]
+                use $crate::runtime::asset_hub_kusama as $para;
+                #[allow(unused_imports)]
+                use $crate::runtime::kusama_relay_chain as $relay;
+                $body
+            }
+            #[cfg(feature = "paseo")]
+            $crate::runtime::Runtime::Paseo => {
+                #[allow(unused_imports)]
+                use $crate::runtime::asset_hub_paseo as $para;
+                #[allow(unused_imports)]
+                use $crate::runtime::paseo_relay_chain as $relay;
+                $body
+            }
+            #[cfg(feature = "westend")]
+            $crate::runtime::Runtime::Westend => {
+                #[allow(unused_imports)]
+                use $crate::runtime::asset_hub_westend as $para;
+                #[allow(unused_imports)]
+                use $crate::runtime::westend_relay_chain as $relay;
+                $body
+            }
+            #[allow(unreachable_patterns)]
+            _ => $unavailable,
+        }
+    };
+}
+
+/// A `ParaInclusion` event, decoupled from the generated types of a single runtime.
+#[derive(Debug, Clone)]
+pub enum InclusionEvent {
+    CandidateBacked {
+        para_id: u32,
+        /// The hash of the candidate receipt, referenced by disputes.
+        candidate_hash: H256,
+        para_head: H256,
+        relay_parent: H256,
+        core_index: u32,
+        group_index: u32,
+    },
+    CandidateIncluded {
+        para_id: u32,
+        para_head: H256,
+        relay_parent: H256,
+        core_index: u32,
+        group_index: u32,
+    },
+    CandidateTimedOut {
+        para_id: u32,
+        para_head: H256,
+        relay_parent: H256,
+        core_index: u32,
+    },
+}
+
+impl InclusionEvent {
+    /// The parachain which produced the candidate.
+    pub fn para_id(&self) -> u32 {
+        match self {
+            InclusionEvent::CandidateBacked { para_id, .. }
+            | InclusionEvent::CandidateIncluded { para_id, .. }
+            | InclusionEvent::CandidateTimedOut { para_id, .. } => *para_id,
+        }
+    }
+
+    /// The hash of the parachain block of the candidate.
+    pub fn para_head(&self) -> H256 {
+        match self {
+            InclusionEvent::CandidateBacked { para_head, .. }
+            | InclusionEvent::CandidateIncluded { para_head, .. }
+            | InclusionEvent::CandidateTimedOut { para_head, .. } => *para_head,
+        }
+    }
+}
+
+/// A `ParasDisputes` event, decoupled from the generated types of a single runtime.
+#[derive(Debug, Clone)]
+pub enum DisputeEvent {
+    Initiated {
+        candidate_hash: H256,
+        /// The dispute was raised by a validator of this chain, rather than imported.
+        local: bool,
+    },
+    Concluded {
+        candidate_hash: H256,
+        valid: bool,
+    },
+    /// The relay chain reverts to the given block after a dispute concluded against a candidate.
+    Revert {
+        block: u32,
+    },
+}
+
+/// An `OnDemandAssignmentProvider` event, decoupled from the generated types of a single runtime.
+#[derive(Debug, Clone)]
+pub enum OnDemandEvent {
+    OrderPlaced {
+        para_id: u32,
+        spot_price: u128,
+        ordered_by: [u8; 32],
+    },
+    SpotPriceSet {
+        spot_price: u128,
+    },
+}
+
+/// An event related to a runtime upgrade of the parachain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpgradeEvent {
+    /// `ParachainSystem::ValidationFunctionStored`: the new code awaits the relay chain go-ahead.
+    ValidationFunctionStored,
+    /// `ParachainSystem::ValidationFunctionApplied`: the new code is used from the next block.
+    ValidationFunctionApplied { relay_chain_block: u32 },
+    /// `System::CodeUpdated` of the parachain.
+    CodeUpdated,
+    /// Relay chain `Paras::CodeUpgradeScheduled` of the parachain.
+    CodeUpgradeScheduled,
+    /// Relay chain `Paras::CurrentCodeUpdated` of the parachain.
+    CurrentCodeUpdated,
+}
+
+impl std::fmt::Display for UpgradeEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpgradeEvent::ValidationFunctionStored => {
+                f.write_str("ParachainSystem.ValidationFunctionStored")
+            }
+            UpgradeEvent::ValidationFunctionApplied { relay_chain_block } => write!(
+                f,
+                "ParachainSystem.ValidationFunctionApplied (relay chain block #{relay_chain_block})"
+            ),
+            UpgradeEvent::CodeUpdated => f.write_str("System.CodeUpdated"),
+            UpgradeEvent::CodeUpgradeScheduled => f.write_str("Paras.CodeUpgradeScheduled"),
+            UpgradeEvent::CurrentCodeUpdated => f.write_str("Paras.CurrentCodeUpdated"),
+        }
+    }
+}
+
+/// The `ParaInherent::enter` data of a relay chain block, restricted to a single parachain.
+#[derive(Debug, Clone, Default)]
+pub struct ParaInherentData {
+    /// Candidates of the parachain backed in this block.
+    pub backed_candidates: Vec<BackedCandidate>,
+    /// The availability bitfields signed by the validators, one bit per core.
+    pub bitfields: Vec<Vec<bool>>,
+    /// Dispute statements of all parachains, as they only reference the candidate hash.
+    pub disputes: Vec<DisputeStatements>,
+}
+
+/// A candidate backed in `ParaInherent::enter`.
+#[derive(Debug, Clone)]
+pub struct BackedCandidate {
+    pub para_head: H256,
+    /// Number of validity votes of the backing group.
+    pub validity_votes: usize,
+    /// Which members of the backing group voted, in group order.
+    pub validator_indices: Vec<bool>,
+}
+
+/// The statements of a dispute included in `ParaInherent::enter`.
+#[derive(Debug, Clone)]
+pub struct DisputeStatements {
+    pub candidate_hash: H256,
+    pub session: u32,
+    pub valid: usize,
+    pub invalid: usize,
+}
+
+/// The async backing parameters of `Configuration::ActiveConfig`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HostConfiguration {
+    /// Maximum number of unincluded ancestors of a candidate, besides the included head.
+    pub max_candidate_depth: u32,
+    /// Number of ancestors of the latest relay chain block a candidate can use as relay parent.
+    pub allowed_ancestry_len: u32,
+    /// Number of relay chain blocks cores are scheduled ahead of time.
+    pub scheduling_lookahead: u32,
+    pub minimum_backing_votes: u32,
+}
+
+impl HostConfiguration {
+    /// Maximum number of relay chain blocks between the relay parent of a candidate
+    /// and the block it is backed in.
+    pub fn max_relay_parent_age(&self) -> u32 {
+        self.allowed_ancestry_len + 1
+    }
+
+    /// Maximum length of the unincluded segment of the parachain, including the candidate.
+    pub fn max_unincluded_segment(&self) -> u32 {
+        self.max_candidate_depth + 1
+    }
+}
+
+impl std::fmt::Display for HostConfiguration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "max_candidate_depth={} allowed_ancestry_len={} scheduling_lookahead={} minimum_backing_votes={}",
+            self.max_candidate_depth,
+            self.allowed_ancestry_len,
+            self.scheduling_lookahead,
+            self.minimum_backing_votes
+        )
+    }
+}
+
+/// The consensus settings of the parachain runtime, at its latest block.
+#[derive(Debug, Clone, Copy)]
+pub struct ParachainConfiguration {
+    /// `Aura::SlotDuration`.
+    pub slot_duration_ms: u64,
+    /// `Timestamp::MinimumPeriod`.
+    pub minimum_period_ms: u64,
+    /// Blocks authored in the current slot, from `AuraExt::SlotInfo`.
+    pub authored_in_slot: Option<u32>,
+    /// Length of `ParachainSystem::UnincludedSegment`.
+    pub unincluded_segment_len: u32,
+    /// `async_backing_params` of `ParachainSystem::HostConfiguration`, as seen from the relay parent.
+    pub async_backing_params: Option<(u32, u32)>,
+}
+
+/// The cores of `ParaScheduler::ClaimQueue` with an assignment of a parachain.
+#[derive(Debug, Clone, Default)]
+pub struct ParaClaims {
+    /// Cores with any assignment of the parachain.
+    pub cores: Vec<u32>,
+    /// Cores with an on-demand (`Assignment::Pool`) assignment of the parachain.
+    pub pool_cores: Vec<u32>,
+}
+
+/// The backing groups of a session, as assigned by `ParaScheduler::ValidatorGroups`.
+#[derive(Debug, Clone)]
+pub struct SessionGroups {
+    pub session: u32,
+    /// The validators of each group, indexed by group index.
+    pub groups: Vec<Vec<Validator>>,
+}
+
+/// A validator actively participating in parachain consensus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Validator {
+    /// The parachain validator key of `ParasShared::ActiveValidatorKeys`.
+    pub key: [u8; 32],
+    /// The index into the session validator set, from `ParasShared::ActiveValidatorIndices`.
+    pub index: u32,
+}
+
+impl SessionGroups {
+    /// The validators of a group, empty if the group is unknown.
+    pub fn members(&self, group: u32) -> &[Validator] {
+        self.groups
+            .get(group as usize)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+impl ParaInherentData {
+    /// The number of bitfields marking `core` as available, out of all bitfields of the block.
+    pub fn availability(&self, core: u32) -> (usize, usize) {
+        let available = self
+            .bitfields
+            .iter()
+            .filter(|bits| bits.get(core as usize).copied().unwrap_or(false))
+            .count();
+
+        (available, self.bitfields.len())
+    }
+
+    /// The backed candidate with the given head.
+    pub fn backed_candidate(&self, para_head: H256) -> Option<&BackedCandidate> {
+        self.backed_candidates
+            .iter()
+            .find(|candidate| candidate.para_head == para_head)
+    }
+}
+
+impl Runtime {
+    /// The cargo feature that compiles in the metadata of this runtime.
+    pub fn feature(&self) -> Option<&'static str> {
+        match self {
+            Runtime::Kusama => None,
+            Runtime::Polkadot => Some("polkadot"),
+            Runtime::Paseo => Some("paseo"),
+            Runtime::Westend => Some("westend"),
+        }
+    }
+
+    /// Returns true if the blocks of this runtime can be decoded, with its own metadata
+    /// or with the [`Runtime::fallback`] one.
+    pub fn is_available(&self) -> bool {
+        match self {
+            Runtime::Kusama | Runtime::Polkadot => true,
+            Runtime::Paseo => cfg!(feature = "paseo"),
+            Runtime::Westend => cfg!(feature = "westend"),
+        }
+    }
+
+    /// The runtime whose metadata decodes this one, when its own is not compiled in.
+    pub fn fallback(&self) -> Option<Runtime> {
+        match self {
+            Runtime::Polkadot if !cfg!(feature = "polkadot") => Some(Runtime::Kusama),
+            _ => None,
+        }
+    }
+
+    /// The file names of the metadata artifacts, as (relay chain, parachain).
+    pub fn artifact_files(&self) -> (&'static str, &'static str) {
+        match self {
+            Runtime::Kusama => ("kusama.scale", "asset_hub_kusama.scale"),
+            Runtime::Polkadot => ("polkadot.scale", "asset_hub_polkadot.scale"),
+            Runtime::Paseo => ("paseo.scale", "asset_hub_paseo.scale"),
+            Runtime::Westend => ("westend.scale", "asset_hub_westend.scale"),
+        }
+    }
+
+    /// The metadata artifacts compiled in, as (relay chain, parachain).
+    fn artifacts(&self) -> Option<(&'static [u8], &'static [u8])> {
+        match self {
+            Runtime::Kusama => Some((
+                include_bytes!("../artifacts/kusama.scale"),
+                include_bytes!("../artifacts/asset_hub_kusama.scale"),
+            )),
+            #[cfg(feature = "polkadot")]
+            Runtime::Polkadot => Some((
+                include_bytes!("../artifacts/polkadot.scale"),
+                include_bytes!("../artifacts/asset_hub_polkadot.scale"),
+            )),
+            #[cfg(feature = "paseo")]
+            Runtime::Paseo => Some((
+                include_bytes!("../artifacts/paseo.scale"),
+                include_bytes!("../artifacts/asset_hub_paseo.scale"),
+            )),
+            #[cfg(feature = "westend")]
+            Runtime::Westend => Some((
+                include_bytes!("../artifacts/westend.scale"),
+                include_bytes!("../artifacts/asset_hub_westend.scale"),
+            )),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    /// The error of decoding with a runtime whose metadata is not compiled in.
+    fn unavailable(&self) -> subxt::Error {
+        subxt::Error::Other(format!(
+            "{self:?} metadata is not compiled in, rebuild with `--features {}`",
+            self.feature().unwrap_or_default()
+        ))
+    }
+
+    /// The spec versions the metadata artifacts were fetched at, as (relay chain, parachain).
+    ///
+    /// Unknown when the metadata of this runtime is not compiled in.
+    pub fn bundled_spec_versions(&self) -> (Option<u32>, Option<u32>) {
+        let Some((relay, para)) = self.artifacts() else {
+            return (None, None);
+        };
+
+        (artifact_spec_version(relay), artifact_spec_version(para))
+    }
+
+    /// Decode the relay parent number of a `ParachainSystem::set_validation_data` extrinsic.
+    pub fn relay_parent_number(&self, ext: &ExtrinsicDetails) -> Option<u32> {
+        with_runtime!(self, None, |_relay, para| {
+            ext.as_extrinsic::<para::parachain_system::calls::types::SetValidationData>()
+                .ok()
+                .flatten()
+                .map(|call| call.data.validation_data.relay_parent_number)
+        })
+    }
+
+    /// Decode the milliseconds of a `Timestamp::set` extrinsic.
+    pub fn timestamp(&self, ext: &ExtrinsicDetails) -> Option<u64> {
+        with_runtime!(self, None, |_relay, para| {
+            ext.as_extrinsic::<para::timestamp::calls::types::Set>()
+                .ok()
+                .flatten()
+                .map(|call| call.now)
+        })
+    }
+
+    /// Fetch the `Timestamp::Now` of a relay chain block.
+    pub async fn relay_timestamp(
+        &self,
+        api: &OnlineClient<PolkadotConfig>,
+        at: H256,
+    ) -> Result<Option<u64>, subxt::Error> {
+        with_runtime!(self, Err(self.unavailable()), |relay, _para| {
+            api.storage()
+                .at(at)
+                .fetch(&relay::storage().timestamp().now())
+                .await
+        })
+    }
+
+    /// Fetch `Configuration::ActiveConfig` of a relay chain block.
+    pub async fn host_configuration(
+        &self,
+        api: &OnlineClient<PolkadotConfig>,
+        at: H256,
+    ) -> Result<Option<HostConfiguration>, subxt::Error> {
+        with_runtime!(self, Err(self.unavailable()), |relay, _para| {
+            let config = api
+                .storage()
+                .at(at)
+                .fetch(&relay::storage().configuration().active_config())
+                .await?;
+
+            Ok(config.map(|config| HostConfiguration {
+                max_candidate_depth: config.async_backing_params.max_candidate_depth,
+                allowed_ancestry_len: config.async_backing_params.allowed_ancestry_len,
+                scheduling_lookahead: config.scheduler_params.lookahead,
+                minimum_backing_votes: config.minimum_backing_votes,
+            }))
+        })
+    }
+
+    /// Fetch the cores with an assignment of `para_id` in `ParaScheduler::ClaimQueue`.
+    pub async fn para_cores(
+        &self,
+        api: &OnlineClient<PolkadotConfig>,
+        at: H256,
+        para_id: u32,
+    ) -> Result<Vec<u32>, subxt::Error> {
+        Ok(self.para_claims(api, at, para_id).await?.cores)
+    }
+
+    /// Fetch the cores with an assignment of `para_id` in `ParaScheduler::ClaimQueue`,
+    /// and which of them are assigned through the on-demand pool.
+    pub async fn para_claims(
+        &self,
+        api: &OnlineClient<PolkadotConfig>,
+        at: H256,
+        para_id: u32,
+    ) -> Result<ParaClaims, subxt::Error> {
+        with_runtime!(self, Err(self.unavailable()), |relay, _para| {
+            use relay::runtime_types::polkadot_runtime_parachains::scheduler::common::Assignment;
+
+            let claim_queue = api
+                .storage()
+                .at(at)
+                .fetch_or_default(&relay::storage().para_scheduler().claim_queue())
+                .await?;
+
+            let mut claims = ParaClaims::default();
+            for (core, assignments) in claim_queue.iter() {
+                let mut assigned = false;
+                let mut pool = false;
+                for assignment in assignments.iter() {
+                    match assignment {
+                        Assignment::Pool { para_id: id, .. } if id.0 == para_id => {
+                            assigned = true;
+                            pool = true;
+                        }
+                        Assignment::Bulk(id) if id.0 == para_id => assigned = true,
+                        // Assignments of other parachains, or variants added by later runtimes.
+                        _ => {}
+                    }
+                }
+
+                if assigned {
+                    claims.cores.push(core.0);
+                }
+                if pool {
+                    claims.pool_cores.push(core.0);
+                }
+            }
+
+            Ok(claims)
+        })
+    }
+
+    /// Fetch the consensus settings of the latest parachain block.
+    pub async fn parachain_configuration(
+        &self,
+        api: &OnlineClient<PolkadotConfig>,
+    ) -> Result<ParachainConfiguration, subxt::Error> {
+        with_runtime!(self, Err(self.unavailable()), |_relay, para| {
+            let constants = api.constants();
+            let slot_duration_ms = constants.at(&para::constants().aura().slot_duration())?;
+            let minimum_period_ms =
+                constants.at(&para::constants().timestamp().minimum_period())?;
+
+            let storage = api.storage().at_latest().await?;
+            let slot_info = storage
+                .fetch(&para::storage().aura_ext().slot_info())
+                .await?;
+            let unincluded_segment = storage
+                .fetch_or_default(&para::storage().parachain_system().unincluded_segment())
+                .await?;
+            let host_configuration = storage
+                .fetch(&para::storage().parachain_system().host_configuration())
+                .await?;
+
+            Ok(ParachainConfiguration {
+                slot_duration_ms,
+                minimum_period_ms,
+                authored_in_slot: slot_info.map(|(_slot, authored)| authored),
+                unincluded_segment_len: unincluded_segment.len() as u32,
+                async_backing_params: host_configuration.map(|config| {
+                    (
+                        config.async_backing_params.max_candidate_depth,
+                        config.async_backing_params.allowed_ancestry_len,
+                    )
+                }),
+            })
+        })
+    }
+
+    /// Fetch the length of `ParachainSystem::UnincludedSegment` after a parachain block.
+    ///
+    /// The segment includes the block itself, unless the parachain does not use async backing.
+    pub async fn unincluded_segment_len(&self, block: &Block) -> Result<u32, subxt::Error> {
+        with_runtime!(self, Err(self.unavailable()), |_relay, para| {
+            let segment = block
+                .storage()
+                .fetch_or_default(&para::storage().parachain_system().unincluded_segment())
+                .await?;
+
+            Ok(segment.len() as u32)
+        })
+    }
+
+    /// Fetch `ParasShared::CurrentSessionIndex` of a relay chain block.
+    pub async fn session_index(
+        &self,
+        api: &OnlineClient<PolkadotConfig>,
+        at: H256,
+    ) -> Result<u32, subxt::Error> {
+        with_runtime!(self, Err(self.unavailable()), |relay, _para| {
+            api.storage()
+                .at(at)
+                .fetch_or_default(&relay::storage().paras_shared().current_session_index())
+                .await
+        })
+    }
+
+    /// Fetch the backing groups of the session of a relay chain block.
+    pub async fn session_groups(
+        &self,
+        api: &OnlineClient<PolkadotConfig>,
+        at: H256,
+    ) -> Result<SessionGroups, subxt::Error> {
+        with_runtime!(self, Err(self.unavailable()), |relay, _para| {
+            let storage = api.storage().at(at);
+            let session = storage
+                .fetch_or_default(&relay::storage().paras_shared().current_session_index())
+                .await?;
+            let groups = storage
+                .fetch_or_default(&relay::storage().para_scheduler().validator_groups())
+                .await?;
+            let indices = storage
+                .fetch_or_default(&relay::storage().paras_shared().active_validator_indices())
+                .await?;
+            let keys = storage
+                .fetch_or_default(&relay::storage().paras_shared().active_validator_keys())
+                .await?;
+
+            // Group members index into the active validators.
+            let groups = groups
+                .into_iter()
+                .map(|group| {
+                    group
+                        .into_iter()
+                        .filter_map(|active| {
+                            let active = active.0 as usize;
+                            Some(Validator {
+                                key: keys.get(active)?.0,
+                                index: indices.get(active)?.0,
+                            })
+                        })
+                        .collect()
+                })
+                .collect();
+
+            Ok(SessionGroups { session, groups })
+        })
+    }
+
+    /// Decode a relay chain `ParaInherent::enter` extrinsic, keeping the candidates of `para_id`.
+    pub fn para_inherent(&self, ext: &ExtrinsicDetails, para_id: u32) -> Option<ParaInherentData> {
+        with_runtime!(self, None, |relay, _para| {
+            let call = ext
+                .as_extrinsic::<relay::para_inherent::calls::types::Enter>()
+                .ok()
+                .flatten()?;
+            let data = call.data;
+
+            let backed_candidates = data
+                .backed_candidates
+                .into_iter()
+                .filter(|backed| backed.candidate.descriptor.para_id.0 == para_id)
+                .map(|backed| BackedCandidate {
+                    para_head: backed.candidate.descriptor.para_head,
+                    validity_votes: backed.validity_votes.len(),
+                    validator_indices: backed.validator_indices.iter().collect(),
+                })
+                .collect();
+
+            let bitfields = data
+                .bitfields
+                .into_iter()
+                .map(|signed| signed.payload.0.iter().collect())
+                .collect();
+
+            let disputes = data
+                .disputes
+                .into_iter()
+                .map(|set| {
+                    let valid = set
+                        .statements
+                        .iter()
+                        // `DisputeStatement` moves between the versioned `polkadot_primitives`
+                        // modules, so it is matched on its variant index: `Valid` comes first.
+                        .filter(|(statement, ..)| statement.encode().first() == Some(&0))
+                        .count();
+
+                    DisputeStatements {
+                        candidate_hash: set.candidate_hash.0,
+                        session: set.session,
+                        valid,
+                        invalid: set.statements.len() - valid,
+                    }
+                })
+                .collect();
+
+            Some(ParaInherentData {
+                backed_candidates,
+                bitfields,
+                disputes,
+            })
+        })
+    }
+
+    /// Decode a relay chain `ParasDisputes` event.
+    pub fn dispute_event(&self, evt: &EventDetails) -> Option<DisputeEvent> {
+        with_runtime!(self, None, |relay, _para| {
+            use relay::runtime_types::polkadot_runtime_parachains::disputes::{
+                DisputeLocation, DisputeResult, pallet::Event,
+            };
+
+            let Ok(relay::Event::ParasDisputes(event)) = evt.as_root_event::<relay::Event>() else {
+                return None;
+            };
+
+            let event = match event {
+                Event::DisputeInitiated(candidate_hash, location) => DisputeEvent::Initiated {
+                    candidate_hash: candidate_hash.0,
+                    local: matches!(location, DisputeLocation::Local),
+                },
+                Event::DisputeConcluded(candidate_hash, result) => DisputeEvent::Concluded {
+                    candidate_hash: candidate_hash.0,
+                    valid: matches!(result, DisputeResult::Valid),
+                },
+                Event::Revert(block) => DisputeEvent::Revert { block },
+            };
+
+            Some(event)
+        })
+    }
+
+    /// Fetch the spec version a parachain block was executed with, from `System::LastRuntimeUpgrade`.
+    pub async fn parachain_spec_version(&self, block: &Block) -> Result<Option<u32>, subxt::Error> {
+        with_runtime!(self, Err(self.unavailable()), |_relay, para| {
+            let upgrade = block
+                .storage()
+                .fetch(&para::storage().system().last_runtime_upgrade())
+                .await?;
+
+            Ok(upgrade.map(|upgrade| upgrade.spec_version.0))
+        })
+    }
+
+    /// Fetch the spec version a relay chain block was executed with, from `System::LastRuntimeUpgrade`.
+    pub async fn relay_spec_version(&self, block: &Block) -> Result<Option<u32>, subxt::Error> {
+        with_runtime!(self, Err(self.unavailable()), |relay, _para| {
+            let upgrade = block
+                .storage()
+                .fetch(&relay::storage().system().last_runtime_upgrade())
+                .await?;
+
+            Ok(upgrade.map(|upgrade| upgrade.spec_version.0))
+        })
+    }
+
+    /// Decode a parachain `ParachainSystem` or `System` event related to a runtime upgrade.
+    pub fn parachain_upgrade_event(&self, evt: &EventDetails) -> Option<UpgradeEvent> {
+        with_runtime!(self, None, |_relay, para| {
+            use para::parachain_system::events::{
+                ValidationFunctionApplied, ValidationFunctionStored,
+            };
+
+            if let Ok(Some(applied)) = evt.as_event::<ValidationFunctionApplied>() {
+                return Some(UpgradeEvent::ValidationFunctionApplied {
+                    relay_chain_block: applied.relay_chain_block_num,
+                });
+            }
+            if let Ok(Some(_)) = evt.as_event::<ValidationFunctionStored>() {
+                return Some(UpgradeEvent::ValidationFunctionStored);
+            }
+            if let Ok(Some(_)) = evt.as_event::<para::system::events::CodeUpdated>() {
+                return Some(UpgradeEvent::CodeUpdated);
+            }
+
+            None
+        })
+    }
+
+    /// Decode a relay chain `Paras` event related to a runtime upgrade of `para_id`.
+    pub fn relay_upgrade_event(&self, evt: &EventDetails, para_id: u32) -> Option<UpgradeEvent> {
+        with_runtime!(self, None, |relay, _para| {
+            use relay::paras::events::{CodeUpgradeScheduled, CurrentCodeUpdated};
+
+            if let Ok(Some(scheduled)) = evt.as_event::<CodeUpgradeScheduled>()
+                && scheduled.0.0 == para_id
+            {
+                return Some(UpgradeEvent::CodeUpgradeScheduled);
+            }
+            if let Ok(Some(updated)) = evt.as_event::<CurrentCodeUpdated>()
+                && updated.0.0 == para_id
+            {
+                return Some(UpgradeEvent::CurrentCodeUpdated);
+            }
+
+            None
+        })
+    }
+
+    /// Decode a relay chain `OnDemandAssignmentProvider` event.
+    pub fn on_demand_event(&self, evt: &EventDetails) -> Option<OnDemandEvent> {
+        with_runtime!(self, None, |relay, _para| {
+            use relay::runtime_types::polkadot_runtime_parachains::on_demand::pallet::Event;
+
+            let Ok(relay::Event::OnDemandAssignmentProvider(event)) =
+                evt.as_root_event::<relay::Event>()
+            else {
+                return None;
+            };
+
+            let event = match event {
+                Event::OnDemandOrderPlaced {
+                    para_id,
+                    spot_price,
+                    ordered_by,
+                } => OnDemandEvent::OrderPlaced {
+                    para_id: para_id.0,
+                    spot_price,
+                    ordered_by: ordered_by.0,
+                },
+                Event::SpotPriceSet { spot_price } => OnDemandEvent::SpotPriceSet { spot_price },
+                // Newer runtimes also report credited accounts.
+                #[allow(unreachable_patterns)]
+                _ => return None,
+            };
+
+            Some(event)
+        })
+    }
+
+    /// Fetch the number of on-demand orders of `para_id` waiting in the queue of a relay chain block.
+    pub async fn on_demand_queued(
+        &self,
+        api: &OnlineClient<PolkadotConfig>,
+        at: H256,
+        para_id: u32,
+    ) -> Result<usize, subxt::Error> {
+        with_runtime!(self, Err(self.unavailable()), |relay, _para| {
+            use relay::runtime_types::polkadot_parachain_primitives::primitives::Id;
+
+            let storage = api.storage().at(at);
+            let on_demand = relay::storage().on_demand_assignment_provider();
+
+            let free = storage.fetch_or_default(&on_demand.free_entries()).await?;
+            let mut queued = free
+                .0
+                .iter()
+                .filter(|order| order.para_id.0 == para_id)
+                .count();
+
+            // Orders bound to the core the parachain last ran on are queued separately.
+            let affinity = storage
+                .fetch(&on_demand.para_id_affinity(Id(para_id)))
+                .await?;
+            if let Some(affinity) = affinity {
+                let entries = storage
+                    .fetch_or_default(&on_demand.affinity_entries(affinity.core_index))
+                    .await?;
+                queued += entries
+                    .0
+                    .iter()
+                    .filter(|order| order.para_id.0 == para_id)
+                    .count();
+            }
+
+            Ok(queued)
+        })
+    }
+
+    /// Decode a relay chain `ParaInclusion` event.
+    pub fn inclusion_event(&self, evt: &EventDetails) -> Option<InclusionEvent> {
+        with_runtime!(self, None, |relay, _para| {
+            use relay::runtime_types::polkadot_runtime_parachains::inclusion::pallet::Event;
+
+            let Ok(relay::Event::ParaInclusion(event)) = evt.as_root_event::<relay::Event>() else {
+                return None;
+            };
+
+            let event = match event {
+                Event::CandidateBacked(receipt, _head, core_index, group_index) => {
+                    let candidate_hash = H256(sp_crypto_hashing::blake2_256(&receipt.encode()));
+                    let descriptor = receipt.descriptor;
+                    InclusionEvent::CandidateBacked {
+                        para_id: descriptor.para_id.0,
+                        candidate_hash,
+                        para_head: descriptor.para_head,
+                        relay_parent: descriptor.relay_parent,
+                        core_index: core_index.0,
+                        group_index: group_index.0,
+                    }
+                }
+                Event::CandidateIncluded(receipt, _head, core_index, group_index) => {
+                    let descriptor = receipt.descriptor;
+                    InclusionEvent::CandidateIncluded {
+                        para_id: descriptor.para_id.0,
+                        para_head: descriptor.para_head,
+                        relay_parent: descriptor.relay_parent,
+                        core_index: core_index.0,
+                        group_index: group_index.0,
+                    }
+                }
+                Event::CandidateTimedOut(receipt, _head, core_index) => {
+                    let descriptor = receipt.descriptor;
+                    InclusionEvent::CandidateTimedOut {
+                        para_id: descriptor.para_id.0,
+                        para_head: descriptor.para_head,
+                        relay_parent: descriptor.relay_parent,
+                        core_index: core_index.0,
+                    }
+                }
+                _ => return None,
+            };
+
+            Some(event)
+        })
+    }
+}
+
+/// Decode the `spec_version` of the `System::Version` constant of a metadata artifact.
+pub fn artifact_spec_version(artifact: &[u8]) -> Option<u32> {
+    let metadata = subxt::Metadata::decode(&mut &artifact[..]).ok()?;
+    let version = metadata
+        .pallet_by_name("System")?
+        .constant_by_name("Version")?
+        .value();
+
+    // `RuntimeVersion` starts with `spec_name`, `impl_name` and `authoring_version`.
+    let (_spec_name, _impl_name, _authoring_version, spec_version) =
+        <(String, String, u32, u32)>::decode(&mut &version[..]).ok()?;
+
+    Some(spec_version)
+}
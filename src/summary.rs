@@ -0,0 +1,344 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::async_backing::AsyncBackingStats;
+use crate::backing::BackingStats;
+use crate::config::NetworkConfig;
+use crate::disputes::DisputeStats;
+use crate::health::HealthStats;
+use crate::on_demand::OnDemandStats;
+use crate::propagation::PropagationStats;
+use crate::timestamp::TimestampAnomaly;
+use crate::window::{Recent, Window};
+
+/// Where and how often the session summary is reported.
+#[derive(Debug, Default, Clone)]
+pub struct SummaryOptions {
+    /// Report the summary periodically, in addition to shutdown.
+    pub interval: Option<Duration>,
+
+    /// Also write the latest summary to this file.
+    pub file: Option<PathBuf>,
+}
+
+/// Statistics aggregated over a Subscribe session.
+///
+/// Counters are cumulative, while the block time distribution is kept for the configured window.
+pub struct SessionStats {
+    started: Instant,
+
+    pub parachain_blocks: u64,
+    pub relay_blocks: u64,
+    pub duplicated_timestamps: u64,
+    /// Number of timestamp anomalies, keyed by kind.
+    timestamp_anomalies: BTreeMap<&'static str, u64>,
+    /// Number of blocks with missing, duplicated or out of order inherents.
+    pub inherent_issues: u64,
+
+    /// Wall-clock time between consecutive live parachain blocks, within the window.
+    block_times_ms: Window<u64>,
+    block_time_samples: u64,
+    block_time_total_ms: u64,
+    block_time_max_ms: u64,
+
+    /// Number of author runs (consecutive blocks of the same author), keyed by run length.
+    pub author_runs: BTreeMap<u32, u64>,
+    /// Length of the ongoing author run.
+    current_run: u32,
+
+    pub candidates_backed: u64,
+    pub candidates_included: u64,
+    pub candidates_timed_out: u64,
+    /// Candidates backed with no more than `minimum_backing_votes` validity votes.
+    pub candidates_at_minimum_votes: u64,
+    /// Relay chain blocks between backing and inclusion of the candidates.
+    availability_wait_samples: u64,
+    availability_wait_total: u64,
+    availability_wait_max: u32,
+
+    /// Relay chain blocks without a core assigned to the parachain.
+    pub relay_blocks_without_core: u64,
+    /// Relay chain blocks with a core assigned to the parachain, but no candidate backed.
+    pub relay_blocks_idle_core: u64,
+
+    /// Backed candidates whose parachain block the RPC node never imported.
+    pub collations_not_imported: u64,
+    /// Parachain blocks imported but not backed within `collation_timeout_blocks`.
+    pub collations_not_backed: u64,
+    /// Parachain blocks imported after their candidate was backed.
+    pub collations_imported_late: u64,
+    collation_timeout_blocks: u32,
+    /// Distinct parachain blocks built by the same author at the same height.
+    pub equivocations: u64,
+
+    /// Per backing group and per validator statistics.
+    pub backing: BackingStats,
+    /// Disputes of the parachain candidates.
+    pub disputes: DisputeStats,
+    /// On-demand orders of the parachain.
+    pub on_demand: OnDemandStats,
+    /// Relay parent ages and unincluded segment lengths against the host configuration.
+    pub async_backing: AsyncBackingStats,
+
+    /// The last spec version changes of the relay chain and the parachain.
+    pub runtime_upgrades: Recent<String>,
+    /// Import lag of the parachain RPC nodes.
+    pub propagation: PropagationStats,
+    /// Issues of the RPC endpoints and slow chains.
+    pub health: HealthStats,
+
+    pub outages: u32,
+    pub outage_duration: Duration,
+}
+
+impl SessionStats {
+    pub fn new(network: &NetworkConfig) -> Self {
+        let window = network.window;
+        let thresholds = &network.thresholds;
+        let nodes = std::iter::once("primary".to_string())
+            .chain(network.parachain_observer_urls.iter().cloned())
+            .collect();
+
+        Self {
+            started: Instant::now(),
+            parachain_blocks: 0,
+            relay_blocks: 0,
+            duplicated_timestamps: 0,
+            timestamp_anomalies: BTreeMap::new(),
+            inherent_issues: 0,
+            block_times_ms: Window::new(window),
+            block_time_samples: 0,
+            block_time_total_ms: 0,
+            block_time_max_ms: 0,
+            author_runs: BTreeMap::new(),
+            current_run: 0,
+            candidates_backed: 0,
+            candidates_included: 0,
+            candidates_timed_out: 0,
+            candidates_at_minimum_votes: 0,
+            availability_wait_samples: 0,
+            availability_wait_total: 0,
+            availability_wait_max: 0,
+            relay_blocks_without_core: 0,
+            relay_blocks_idle_core: 0,
+            collations_not_imported: 0,
+            collations_not_backed: 0,
+            collations_imported_late: 0,
+            collation_timeout_blocks: thresholds.collation_timeout_blocks,
+            equivocations: 0,
+            backing: BackingStats::default(),
+            disputes: DisputeStats::default(),
+            on_demand: OnDemandStats::default(),
+            async_backing: AsyncBackingStats::new(thresholds.async_backing_underuse_blocks),
+            runtime_upgrades: Recent::default(),
+            propagation: PropagationStats::new(nodes),
+            health: HealthStats::default(),
+            outages: 0,
+            outage_duration: Duration::ZERO,
+        }
+    }
+
+    /// Record the time elapsed since the previous parachain block.
+    pub fn record_block_time(&mut self, number: u32, block_time_ms: u64) {
+        self.block_times_ms.prune(number, |_, _| {});
+        self.block_times_ms.push(number, block_time_ms);
+
+        self.block_time_samples += 1;
+        self.block_time_total_ms += block_time_ms;
+        self.block_time_max_ms = self.block_time_max_ms.max(block_time_ms);
+    }
+
+    /// Record a timestamp anomaly of a parachain block.
+    pub fn record_timestamp_anomaly(&mut self, anomaly: &TimestampAnomaly) {
+        *self.timestamp_anomalies.entry(anomaly.name()).or_default() += 1;
+    }
+
+    /// Record the relay chain blocks a candidate waited for availability.
+    pub fn record_availability_wait(&mut self, relay_blocks: u32) {
+        self.availability_wait_samples += 1;
+        self.availability_wait_total += relay_blocks as u64;
+        self.availability_wait_max = self.availability_wait_max.max(relay_blocks);
+    }
+
+    /// Record the author of a parachain block.
+    pub fn record_author(&mut self, same_author: bool) {
+        if !same_author && self.current_run > 0 {
+            *self.author_runs.entry(self.current_run).or_default() += 1;
+            self.current_run = 0;
+        }
+
+        self.current_run += 1;
+    }
+
+    /// Render the summary of the session so far.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let mut line = |text: String| {
+            out.push_str(&text);
+            out.push('\n');
+        };
+
+        line(format!(
+            "===== Session summary (uptime {:?}) =====",
+            self.started.elapsed()
+        ));
+        line(format!(
+            " Parachain blocks: {} | Relay blocks: {}",
+            self.parachain_blocks, self.relay_blocks
+        ));
+        line(format!(
+            " Duplicated timestamps: {} / {} ({:.2}%)",
+            self.duplicated_timestamps,
+            self.parachain_blocks,
+            percent(self.duplicated_timestamps, self.parachain_blocks)
+        ));
+
+        let anomalies = self
+            .timestamp_anomalies
+            .iter()
+            .map(|(name, count)| format!("{name} x {count}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        line(format!(" Timestamp anomalies: {anomalies}"));
+        line(format!(
+            " Blocks with inherent issues: {}",
+            self.inherent_issues
+        ));
+
+        let mut block_times = self.block_times_ms.values().copied().collect::<Vec<_>>();
+        block_times.sort_unstable();
+        if block_times.is_empty() {
+            line(" Block time: no samples".into());
+        } else {
+            let average = self.block_time_total_ms as f64 / self.block_time_samples as f64;
+            line(format!(
+                " Block time: avg {:.2}s | max {:.2}s",
+                average / 1000.0,
+                self.block_time_max_ms as f64 / 1000.0,
+            ));
+            line(format!(
+                " Block time (last {} samples): p50 {:.2}s | p90 {:.2}s | p99 {:.2}s | max {:.2}s",
+                self.block_times_ms.len(),
+                percentile(&block_times, 50.0) as f64 / 1000.0,
+                percentile(&block_times, 90.0) as f64 / 1000.0,
+                percentile(&block_times, 99.0) as f64 / 1000.0,
+                percentile(&block_times, 100.0) as f64 / 1000.0,
+            ));
+        }
+
+        let mut author_runs = self.author_runs.clone();
+        if self.current_run > 0 {
+            *author_runs.entry(self.current_run).or_default() += 1;
+        }
+        let runs = author_runs
+            .iter()
+            .map(|(length, count)| format!("{length} in a row x {count}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        line(format!(" Author runs: {runs}"));
+
+        line(format!(
+            " Candidates: backed {} | included {} | timed out {}",
+            self.candidates_backed, self.candidates_included, self.candidates_timed_out
+        ));
+        line(format!(
+            " Backed with the minimum votes: {} | availability wait: avg {:.2} | max {} relay blocks",
+            self.candidates_at_minimum_votes,
+            self.availability_wait_total as f64 / self.availability_wait_samples.max(1) as f64,
+            self.availability_wait_max
+        ));
+        line(format!(
+            " Relay blocks without a core: {} | with a core but no candidate backed: {}",
+            self.relay_blocks_without_core, self.relay_blocks_idle_core
+        ));
+        line(format!(
+            " Collations: backed but never imported {} | imported but not backed within {} relay blocks {} | imported after backing {}",
+            self.collations_not_imported,
+            self.collation_timeout_blocks,
+            self.collations_not_backed,
+            self.collations_imported_late
+        ));
+        line(format!(" Collator equivocations: {}", self.equivocations));
+        for on_demand in self.on_demand.render() {
+            line(on_demand);
+        }
+        line(" Backing groups:".into());
+        for group in self.backing.render() {
+            line(group);
+        }
+        for dispute in self.disputes.render() {
+            line(dispute);
+        }
+        for async_backing in self.async_backing.render() {
+            line(async_backing);
+        }
+        for propagation in self.propagation.render() {
+            line(propagation);
+        }
+        for health in self.health.render() {
+            line(health);
+        }
+        line(format!(
+            " Runtime upgrades: {}",
+            self.runtime_upgrades.total()
+        ));
+        for upgrade in self.runtime_upgrades.iter() {
+            line(format!("   {upgrade}"));
+        }
+        line(format!(
+            " Outages: {} ({:?})",
+            self.outages, self.outage_duration
+        ));
+
+        out
+    }
+
+    /// Print the summary and write it to the configured file.
+    pub fn report(&self, options: &SummaryOptions) {
+        let summary = self.render();
+        println!("{summary}");
+
+        if let Some(path) = &options.file
+            && let Err(err) = std::fs::write(path, &summary)
+        {
+            println!("[!] Failed to write summary to {}: {err}\n", path.display());
+        }
+    }
+}
+
+pub fn percent(value: u64, total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+
+    value as f64 / total as f64 * 100.0
+}
+
+/// Nearest-rank percentile of sorted, non-empty values.
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentile_uses_the_nearest_rank() {
+        let sorted = (1..=10).map(|value| value * 100).collect::<Vec<_>>();
+
+        assert_eq!(percentile(&sorted, 50.0), 500);
+        assert_eq!(percentile(&sorted, 90.0), 900);
+        assert_eq!(percentile(&sorted, 99.0), 1_000);
+        assert_eq!(percentile(&sorted, 0.0), 100);
+        assert_eq!(percentile(&[42], 99.0), 42);
+    }
+
+    #[test]
+    fn percent_of_nothing_is_zero() {
+        assert_eq!(percent(1, 4), 25.0);
+        assert_eq!(percent(3, 0), 0.0);
+    }
+}
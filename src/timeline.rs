@@ -0,0 +1,318 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use subxt::utils::H256;
+use subxt::{OnlineClient, PolkadotConfig};
+
+use crate::async_backing::Samples;
+use crate::config::NetworkConfig;
+use crate::runtime::InclusionEvent;
+
+/// Relay chain blocks after the relay parent searched for the backing of a candidate,
+/// when the host configuration is unknown.
+const DEFAULT_BACKING_SEARCH_BLOCKS: u32 = 4;
+
+/// Where the candidate of a parachain block went on the relay chain.
+#[derive(Debug, Clone, Copy)]
+pub struct Timeline {
+    pub relay_parent: u32,
+    pub backed: Option<u32>,
+    pub included: Option<u32>,
+    /// The search reached the relay chain head before finding the inclusion.
+    pub pending: bool,
+    /// Relay chain blocks searched for the backing after the relay parent.
+    pub backing_search: u32,
+    /// Relay chain blocks searched for the inclusion after the backing.
+    pub inclusion_search: u32,
+}
+
+impl Timeline {
+    /// Neither included within the searched relay chain blocks nor waiting for inclusion.
+    pub fn not_found(&self) -> bool {
+        self.included.is_none() && !self.pending
+    }
+}
+
+impl fmt::Display for Timeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "relay parent #{}", self.relay_parent)?;
+        match self.backed {
+            Some(backed) => write!(f, " -> backed #{backed} (+{})", backed - self.relay_parent)?,
+            None if self.pending => f.write_str(" -> not backed")?,
+            None => write!(
+                f,
+                " -> backing not found within {} relay blocks",
+                self.backing_search
+            )?,
+        }
+        match (self.backed, self.included) {
+            (Some(backed), Some(included)) => {
+                write!(f, " -> included #{included} (+{})", included - backed)?
+            }
+            (Some(_), None) if self.pending => f.write_str(" -> not included")?,
+            (Some(_), None) => write!(
+                f,
+                " -> inclusion not found within {} relay blocks",
+                self.inclusion_search
+            )?,
+            _ => {}
+        }
+        if self.pending {
+            f.write_str(" (pending)")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The `ParaInclusion` events of the parachain in the relay chain blocks already fetched.
+pub struct RelayInclusions<'a> {
+    relay_api: &'a OnlineClient<PolkadotConfig>,
+    relay_legacy_methods: &'a subxt_rpcs::LegacyRpcMethods<PolkadotConfig>,
+    network: &'a NetworkConfig,
+    /// The relay chain head when the search started, later blocks do not exist yet.
+    head: u32,
+    events: BTreeMap<u32, Vec<InclusionEvent>>,
+}
+
+impl<'a> RelayInclusions<'a> {
+    pub fn new(
+        relay_api: &'a OnlineClient<PolkadotConfig>,
+        relay_legacy_methods: &'a subxt_rpcs::LegacyRpcMethods<PolkadotConfig>,
+        network: &'a NetworkConfig,
+        head: u32,
+    ) -> Self {
+        Self {
+            relay_api,
+            relay_legacy_methods,
+            network,
+            head,
+            events: BTreeMap::new(),
+        }
+    }
+
+    /// Find the relay chain blocks backing and including the parachain block `para_head`.
+    ///
+    /// The backing is searched within `max_relay_parent_age` relay blocks of the relay parent.
+    pub async fn timeline(
+        &mut self,
+        para_head: H256,
+        relay_parent: u32,
+        max_relay_parent_age: Option<u32>,
+    ) -> Result<Timeline, Box<dyn std::error::Error>> {
+        let backing_search = max_relay_parent_age.unwrap_or(DEFAULT_BACKING_SEARCH_BLOCKS);
+        let inclusion_search = self.network.thresholds.inclusion_search_blocks;
+        let mut timeline = Timeline {
+            relay_parent,
+            backed: None,
+            included: None,
+            pending: false,
+            backing_search,
+            inclusion_search,
+        };
+
+        for number in relay_parent + 1..=relay_parent + backing_search {
+            if number > self.head {
+                timeline.pending = true;
+                return Ok(timeline);
+            }
+            if self.contains(number, para_head, true).await? {
+                timeline.backed = Some(number);
+                break;
+            }
+        }
+
+        let Some(backed) = timeline.backed else {
+            return Ok(timeline);
+        };
+        for number in backed + 1..=backed + inclusion_search {
+            if number > self.head {
+                timeline.pending = true;
+                break;
+            }
+            if self.contains(number, para_head, false).await? {
+                timeline.included = Some(number);
+                break;
+            }
+        }
+
+        Ok(timeline)
+    }
+
+    /// Forget the relay chain blocks before `number`, relay parents only move forward.
+    pub fn prune(&mut self, number: u32) {
+        self.events = self.events.split_off(&number);
+    }
+
+    /// Whether relay chain block `number` backed (or included) the parachain block `para_head`.
+    async fn contains(
+        &mut self,
+        number: u32,
+        para_head: H256,
+        backed: bool,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        if !self.events.contains_key(&number) {
+            let events = self.fetch(number).await?;
+            self.events.insert(number, events);
+        }
+
+        Ok(self.events[&number].iter().any(|event| match event {
+            InclusionEvent::CandidateBacked {
+                para_head: head, ..
+            } => backed && *head == para_head,
+            InclusionEvent::CandidateIncluded {
+                para_head: head, ..
+            } => !backed && *head == para_head,
+            InclusionEvent::CandidateTimedOut { .. } => false,
+        }))
+    }
+
+    async fn fetch(&self, number: u32) -> Result<Vec<InclusionEvent>, Box<dyn std::error::Error>> {
+        let Some(hash) = self
+            .relay_legacy_methods
+            .chain_get_block_hash(Some(number.into()))
+            .await?
+        else {
+            return Ok(Vec::new());
+        };
+
+        let events = self.relay_api.blocks().at(hash).await?.events().await?;
+        let mut inclusions = Vec::new();
+        for evt in events.iter() {
+            if let Some(event) = self.network.runtime.inclusion_event(&evt?)
+                && event.para_id() == self.network.para_id
+            {
+                inclusions.push(event);
+            }
+        }
+
+        Ok(inclusions)
+    }
+}
+
+/// Relay parent to backing and backing to inclusion distances over a range of parachain blocks.
+#[derive(Debug, Default)]
+pub struct TimelineStats {
+    backing: Samples,
+    inclusion: Samples,
+    pending: u64,
+    /// Canonical parachain blocks whose backing or inclusion was not found, with their timeline.
+    not_found: Vec<(u32, H256, Timeline)>,
+    /// Parachain blocks of the forks abandoned for the canonical chain.
+    dropped: Vec<(u32, H256)>,
+}
+
+impl TimelineStats {
+    pub fn record(&mut self, number: u32, hash: H256, timeline: &Timeline) {
+        if let Some(backed) = timeline.backed {
+            self.backing.record(backed - timeline.relay_parent);
+            if let Some(included) = timeline.included {
+                self.inclusion.record(included - backed);
+            }
+        }
+        if timeline.pending {
+            self.pending += 1;
+        }
+        if timeline.not_found() {
+            self.not_found.push((number, hash, *timeline));
+        }
+    }
+
+    /// Record a block of a fork at height `number`, not part of the canonical chain.
+    pub fn record_dropped(&mut self, number: u32, hash: H256) {
+        self.dropped.push((number, hash));
+    }
+
+    pub fn render(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!(
+                " Relay parent to backing: avg {:.2} | max {} relay blocks ({} backed)",
+                self.backing.average(),
+                self.backing.max,
+                self.backing.count
+            ),
+            format!(
+                " Backing to inclusion: avg {:.2} | max {} relay blocks ({} included) | pending {}",
+                self.inclusion.average(),
+                self.inclusion.max,
+                self.inclusion.count,
+                self.pending
+            ),
+            format!(
+                " Canonical blocks without backing or inclusion found: {}",
+                self.not_found.len()
+            ),
+        ];
+        lines.extend(
+            self.not_found
+                .iter()
+                .map(|(number, hash, timeline)| format!("   #{number} {hash:?}: {timeline}")),
+        );
+        lines.push(format!(
+            " Dropped fork blocks, not canonical: {}",
+            self.dropped.len()
+        ));
+        lines.extend(
+            self.dropped
+                .iter()
+                .map(|(number, hash)| format!("   #{number} {hash:?}")),
+        );
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timeline(backed: Option<u32>, included: Option<u32>, pending: bool) -> Timeline {
+        Timeline {
+            relay_parent: 100,
+            backed,
+            included,
+            pending,
+            backing_search: 4,
+            inclusion_search: 10,
+        }
+    }
+
+    #[test]
+    fn missing_backing_and_inclusion_report_the_search() {
+        assert_eq!(
+            timeline(None, None, false).to_string(),
+            "relay parent #100 -> backing not found within 4 relay blocks"
+        );
+        assert_eq!(
+            timeline(Some(101), None, false).to_string(),
+            "relay parent #100 -> backed #101 (+1) -> inclusion not found within 10 relay blocks"
+        );
+        assert_eq!(
+            timeline(Some(101), None, true).to_string(),
+            "relay parent #100 -> backed #101 (+1) -> not included (pending)"
+        );
+    }
+
+    #[test]
+    fn not_found_and_dropped_blocks_are_listed_apart() {
+        let mut stats = TimelineStats::default();
+        stats.record(
+            5,
+            H256::repeat_byte(5),
+            &timeline(Some(101), Some(102), false),
+        );
+        stats.record(6, H256::repeat_byte(6), &timeline(None, None, false));
+        stats.record(7, H256::repeat_byte(7), &timeline(Some(103), None, true));
+        stats.record_dropped(6, H256::repeat_byte(16));
+
+        let lines = stats.render();
+        assert_eq!(
+            lines[2],
+            " Canonical blocks without backing or inclusion found: 1"
+        );
+        assert!(lines[3].starts_with("   #6 "));
+        assert_eq!(lines[4], " Dropped fork blocks, not canonical: 1");
+        assert_eq!(lines[5], format!("   #6 {:?}", H256::repeat_byte(16)));
+        assert_eq!(lines.len(), 6);
+    }
+}
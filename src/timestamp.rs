@@ -0,0 +1,270 @@
+use subxt::config::substrate::{Digest, DigestItem};
+
+use crate::config::TimestampLimits;
+use crate::runtime::RELAY_CHAIN_SLOT_DURATION_MS;
+
+const BABE_ENGINE_ID: [u8; 4] = *b"BABE";
+
+/// The clock of the relay parent a parachain timestamp is compared with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelayClock {
+    /// `Timestamp::Now` of the relay parent.
+    Timestamp,
+    /// Start of the BABE slot of the relay parent.
+    BabeSlot,
+}
+
+impl std::fmt::Display for RelayClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RelayClock::Timestamp => f.write_str("timestamp"),
+            RelayClock::BabeSlot => f.write_str("BABE slot"),
+        }
+    }
+}
+
+/// The time of the relay parent of a parachain block, by both clocks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RelayParentTime {
+    pub timestamp_ms: Option<u64>,
+    pub slot_ms: Option<u64>,
+}
+
+impl RelayParentTime {
+    fn clocks(&self) -> impl Iterator<Item = (RelayClock, u64)> {
+        [
+            (RelayClock::Timestamp, self.timestamp_ms),
+            (RelayClock::BabeSlot, self.slot_ms),
+        ]
+        .into_iter()
+        .filter_map(|(clock, time)| Some((clock, time?)))
+    }
+
+    /// Describe the drift of a parachain timestamp against each known clock.
+    pub fn render_drift(&self, timestamp: u64) -> Option<String> {
+        let drifts = self
+            .clocks()
+            .map(|(clock, time)| format!("{clock} drift {:+}ms", drift_ms(timestamp, time)))
+            .collect::<Vec<_>>();
+
+        (!drifts.is_empty()).then(|| drifts.join(" | "))
+    }
+}
+
+/// The start of the BABE slot of a relay chain block, from its pre-runtime digest.
+pub fn babe_slot_ms(digest: &Digest) -> Option<u64> {
+    let payload = digest.logs.iter().find_map(|log| match log {
+        DigestItem::PreRuntime(id, payload) if *id == BABE_ENGINE_ID => Some(payload),
+        _ => None,
+    })?;
+
+    // Every `PreDigest` variant starts with the authority index and the slot.
+    let slot = payload.get(5..13)?;
+    let slot = u64::from_le_bytes(slot.try_into().ok()?);
+
+    Some(slot * RELAY_CHAIN_SLOT_DURATION_MS)
+}
+
+/// An unexpected parachain `Timestamp.set` value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampAnomaly {
+    /// Same value as the previous parachain block.
+    Repeated,
+    /// Lower value than the previous parachain block.
+    Regressed { by_ms: u64 },
+    /// Further ahead of the relay parent clock than allowed.
+    Ahead { clock: RelayClock, drift_ms: i64 },
+    /// Behind the relay parent clock by more than allowed.
+    Lagging { clock: RelayClock, drift_ms: i64 },
+}
+
+impl TimestampAnomaly {
+    /// Short name used to aggregate anomalies.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TimestampAnomaly::Repeated => "repeated",
+            TimestampAnomaly::Regressed { .. } => "regressed",
+            TimestampAnomaly::Ahead {
+                clock: RelayClock::Timestamp,
+                ..
+            } => "ahead",
+            TimestampAnomaly::Ahead {
+                clock: RelayClock::BabeSlot,
+                ..
+            } => "ahead of slot",
+            TimestampAnomaly::Lagging {
+                clock: RelayClock::Timestamp,
+                ..
+            } => "lagging",
+            TimestampAnomaly::Lagging {
+                clock: RelayClock::BabeSlot,
+                ..
+            } => "lagging slot",
+        }
+    }
+}
+
+impl std::fmt::Display for TimestampAnomaly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimestampAnomaly::Repeated => write!(f, "Timestamp repeats the previous block"),
+            TimestampAnomaly::Regressed { by_ms } => {
+                write!(f, "Timestamp regressed by {by_ms}ms")
+            }
+            TimestampAnomaly::Ahead { clock, drift_ms } => {
+                write!(
+                    f,
+                    "Timestamp ahead of the relay parent {clock} by {drift_ms}ms"
+                )
+            }
+            TimestampAnomaly::Lagging { clock, drift_ms } => {
+                write!(
+                    f,
+                    "Timestamp lags the relay parent {clock} by {}ms",
+                    -drift_ms
+                )
+            }
+        }
+    }
+}
+
+/// Drift of a parachain timestamp against the time of its relay parent.
+pub fn drift_ms(timestamp: u64, relay_parent_time: u64) -> i64 {
+    timestamp as i64 - relay_parent_time as i64
+}
+
+/// Check a parachain timestamp against the previous parachain block and its relay parent.
+pub fn check(
+    timestamp: u64,
+    previous: Option<u64>,
+    relay_parent: RelayParentTime,
+    limits: TimestampLimits,
+) -> Vec<TimestampAnomaly> {
+    let mut anomalies = Vec::new();
+
+    if let Some(previous) = previous {
+        if timestamp == previous {
+            anomalies.push(TimestampAnomaly::Repeated);
+        } else if timestamp < previous {
+            anomalies.push(TimestampAnomaly::Regressed {
+                by_ms: previous - timestamp,
+            });
+        }
+    }
+
+    for (clock, time) in relay_parent.clocks() {
+        let drift_ms = drift_ms(timestamp, time);
+        if drift_ms > limits.ahead_ms as i64 {
+            anomalies.push(TimestampAnomaly::Ahead { clock, drift_ms });
+        } else if -drift_ms > limits.lag_ms as i64 {
+            anomalies.push(TimestampAnomaly::Lagging { clock, drift_ms });
+        }
+    }
+
+    anomalies
+}
+
+/// Convert a `Timestamp.set` value to a human-readable date.
+pub fn to_datetime(timestamp_ms: u64) -> chrono::DateTime<chrono::Utc> {
+    use chrono::TimeZone;
+
+    let seconds = (timestamp_ms / 1_000) as i64;
+    let nanos = ((timestamp_ms % 1_000) * 1_000_000) as u32;
+    chrono::Utc
+        .timestamp_opt(seconds, nanos)
+        .single()
+        .expect("Failed to convert timestamp to human-readable format; this should not happen")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: TimestampLimits = TimestampLimits {
+        ahead_ms: 18_000,
+        lag_ms: 6_000,
+    };
+
+    fn relay_parent(timestamp_ms: u64) -> RelayParentTime {
+        RelayParentTime {
+            timestamp_ms: Some(timestamp_ms),
+            slot_ms: None,
+        }
+    }
+
+    #[test]
+    fn repeated_and_regressed_timestamps() {
+        let none = RelayParentTime::default();
+        assert_eq!(
+            check(1_000, Some(1_000), none, LIMITS),
+            vec![TimestampAnomaly::Repeated]
+        );
+        assert_eq!(
+            check(1_000, Some(1_500), none, LIMITS),
+            vec![TimestampAnomaly::Regressed { by_ms: 500 }]
+        );
+        assert!(check(1_500, Some(1_000), none, LIMITS).is_empty());
+    }
+
+    #[test]
+    fn drift_within_the_limits() {
+        assert!(check(118_000, None, relay_parent(100_000), LIMITS).is_empty());
+        assert!(check(94_000, None, relay_parent(100_000), LIMITS).is_empty());
+    }
+
+    #[test]
+    fn drift_beyond_the_limits() {
+        assert_eq!(
+            check(118_001, None, relay_parent(100_000), LIMITS),
+            vec![TimestampAnomaly::Ahead {
+                clock: RelayClock::Timestamp,
+                drift_ms: 18_001
+            }]
+        );
+        assert_eq!(
+            check(93_999, None, relay_parent(100_000), LIMITS),
+            vec![TimestampAnomaly::Lagging {
+                clock: RelayClock::Timestamp,
+                drift_ms: -6_001
+            }]
+        );
+    }
+
+    #[test]
+    fn both_relay_clocks_are_checked() {
+        let relay_parent = RelayParentTime {
+            timestamp_ms: Some(100_000),
+            slot_ms: Some(90_000),
+        };
+
+        assert_eq!(
+            check(110_000, None, relay_parent, LIMITS),
+            vec![TimestampAnomaly::Ahead {
+                clock: RelayClock::BabeSlot,
+                drift_ms: 20_000
+            }]
+        );
+        assert_eq!(
+            relay_parent.render_drift(110_000).as_deref(),
+            Some("timestamp drift +10000ms | BABE slot drift +20000ms")
+        );
+        assert_eq!(RelayParentTime::default().render_drift(110_000), None);
+    }
+
+    #[test]
+    fn babe_slot_from_the_pre_runtime_digest() {
+        // Secondary VRF pre-digest: variant, authority index and slot.
+        let mut payload = vec![3, 7, 0, 0, 0];
+        payload.extend_from_slice(&100u64.to_le_bytes());
+        payload.extend_from_slice(&[0; 64]);
+        let digest = Digest {
+            logs: vec![
+                DigestItem::PreRuntime(*b"aura", vec![0; 8]),
+                DigestItem::PreRuntime(BABE_ENGINE_ID, payload),
+            ],
+        };
+
+        assert_eq!(babe_slot_ms(&digest), Some(600_000));
+        assert_eq!(babe_slot_ms(&Digest { logs: vec![] }), None);
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt;
+
+/// A change of the spec version between consecutive blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpecChange {
+    pub from: u32,
+    pub to: u32,
+}
+
+impl fmt::Display for SpecChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "spec {} -> {}", self.from, self.to)
+    }
+}
+
+/// Follows the spec version of a chain across its blocks.
+#[derive(Debug)]
+pub struct SpecTracker {
+    /// The spec version of the bundled metadata artifact.
+    bundled: Option<u32>,
+    current: Option<u32>,
+}
+
+impl SpecTracker {
+    pub fn new(bundled: Option<u32>) -> Self {
+        Self {
+            bundled,
+            current: None,
+        }
+    }
+
+    pub fn current(&self) -> Option<u32> {
+        self.current
+    }
+
+    /// Record the spec version of the next block.
+    ///
+    /// Returns the change against the previous block with a known spec version.
+    pub fn update(&mut self, spec_version: Option<u32>) -> Option<SpecChange> {
+        let to = spec_version?;
+        let from = self.current.replace(to)?;
+
+        (from != to).then_some(SpecChange { from, to })
+    }
+
+    /// A warning when the current spec version differs from the bundled metadata.
+    pub fn bundled_mismatch(&self) -> Option<String> {
+        let (current, bundled) = self.current.zip(self.bundled)?;
+        if current == bundled {
+            return None;
+        }
+
+        Some(format!(
+            "spec {current} does not match the bundled metadata (spec {bundled}), decoding may fail: refresh it with update-metadata"
+        ))
+    }
+}
+
+/// Format an optional spec version.
+pub fn format_spec(spec_version: Option<u32>) -> String {
+    spec_version
+        .map(|spec_version| spec_version.to_string())
+        .unwrap_or_else(|| "unknown".into())
+}
+
+/// Statistics of a range of consecutive blocks built with the same spec version.
+#[derive(Debug)]
+pub struct Segment {
+    pub spec_version: Option<u32>,
+    pub first_block: u32,
+    pub last_block: u32,
+    pub blocks: u32,
+    pub duplicated_timestamps: u32,
+    pub timestamp_anomalies: u32,
+    pub inherent_issues: u32,
+    block_time_total_ms: u64,
+    block_times: u32,
+}
+
+impl Segment {
+    pub fn new(spec_version: Option<u32>, first_block: u32) -> Self {
+        Self {
+            spec_version,
+            first_block,
+            last_block: first_block,
+            blocks: 0,
+            duplicated_timestamps: 0,
+            timestamp_anomalies: 0,
+            inherent_issues: 0,
+            block_time_total_ms: 0,
+            block_times: 0,
+        }
+    }
+
+    /// Record a block of the segment, with its time since the previous block if known.
+    pub fn record_block(&mut self, number: u32, block_time_ms: Option<u64>) {
+        self.blocks += 1;
+        self.last_block = number;
+        if let Some(block_time_ms) = block_time_ms {
+            self.block_time_total_ms += block_time_ms;
+            self.block_times += 1;
+        }
+    }
+
+    pub fn render(&self) -> String {
+        format!(
+            "   spec {}: blocks #{}..=#{} ({}) | avg block time {:.2}s | duplicated timestamps {} | timestamp anomalies {} | inherent issues {}",
+            format_spec(self.spec_version),
+            self.first_block,
+            self.last_block,
+            self.blocks,
+            self.block_time_total_ms as f64 / self.block_times.max(1) as f64 / 1000.0,
+            self.duplicated_timestamps,
+            self.timestamp_anomalies,
+            self.inherent_issues
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_spec_version_is_not_a_change() {
+        let mut tracker = SpecTracker::new(None);
+
+        assert_eq!(tracker.update(Some(1_000)), None);
+        assert_eq!(tracker.current(), Some(1_000));
+    }
+
+    #[test]
+    fn spec_version_changes() {
+        let mut tracker = SpecTracker::new(None);
+        tracker.update(Some(1_000));
+
+        assert_eq!(tracker.update(Some(1_000)), None);
+        assert_eq!(
+            tracker.update(Some(1_001)),
+            Some(SpecChange {
+                from: 1_000,
+                to: 1_001
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_spec_versions_are_skipped() {
+        let mut tracker = SpecTracker::new(None);
+        tracker.update(Some(1_000));
+
+        assert_eq!(tracker.update(None), None);
+        assert_eq!(tracker.current(), Some(1_000));
+        assert_eq!(
+            tracker.update(Some(1_001)),
+            Some(SpecChange {
+                from: 1_000,
+                to: 1_001
+            })
+        );
+    }
+
+    #[test]
+    fn mismatch_with_the_bundled_metadata() {
+        let mut tracker = SpecTracker::new(Some(1_000));
+        assert_eq!(tracker.bundled_mismatch(), None);
+
+        tracker.update(Some(1_000));
+        assert_eq!(tracker.bundled_mismatch(), None);
+
+        tracker.update(Some(1_001));
+        assert!(tracker.bundled_mismatch().is_some());
+        assert!(SpecTracker::new(None).bundled_mismatch().is_none());
+    }
+
+    #[test]
+    fn segment_average_block_time() {
+        let mut segment = Segment::new(Some(1_000), 10);
+        segment.record_block(10, None);
+        segment.record_block(11, Some(6_000));
+        segment.record_block(12, Some(12_000));
+
+        assert_eq!((segment.blocks, segment.last_block), (3, 12));
+        assert!(segment.render().contains("avg block time 9.00s"));
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+/// The history kept by long-running monitors.
+///
+/// Entries are pruned once they fall out of either bound.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct WindowConfig {
+    /// Keep entries of the last N blocks.
+    pub blocks: Option<u32>,
+
+    /// Keep entries seen in the last N seconds.
+    pub seconds: Option<u64>,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            // One day of 6s blocks.
+            blocks: Some(14_400),
+            seconds: None,
+        }
+    }
+}
+
+struct WindowEntry<T> {
+    number: u32,
+    seen: Instant,
+    value: T,
+}
+
+/// Values keyed by block number, pruned by a [`WindowConfig`].
+pub struct Window<T> {
+    config: WindowConfig,
+    entries: VecDeque<WindowEntry<T>>,
+}
+
+impl<T> Window<T> {
+    pub fn new(config: WindowConfig) -> Self {
+        Self {
+            config,
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// Push the value of the given block.
+    pub fn push(&mut self, number: u32, value: T) {
+        self.entries.push_back(WindowEntry {
+            number,
+            seen: Instant::now(),
+            value,
+        });
+    }
+
+    /// Remove the entries outside of the window ending at block `number`.
+    pub fn prune(&mut self, number: u32, mut evicted: impl FnMut(u32, T)) {
+        let now = Instant::now();
+        let max_age = self.config.seconds.map(Duration::from_secs);
+
+        while let Some(entry) = self.entries.front() {
+            let too_old = self
+                .config
+                .blocks
+                .is_some_and(|blocks| number.saturating_sub(entry.number) >= blocks)
+                || max_age.is_some_and(|max_age| now.duration_since(entry.seen) > max_age);
+            if !too_old {
+                break;
+            }
+
+            if let Some(entry) = self.entries.pop_front() {
+                evicted(entry.number, entry.value);
+            }
+        }
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.entries.iter().map(|entry| &entry.value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// Number of entries kept by default by [`Recent`], enough for a summary.
+const RECENT_ENTRIES: usize = 20;
+
+/// The last entries of an unbounded series, with the number of entries ever pushed.
+#[derive(Debug, Clone)]
+pub struct Recent<T> {
+    capacity: usize,
+    pushed: u64,
+    entries: VecDeque<T>,
+}
+
+impl<T> Default for Recent<T> {
+    fn default() -> Self {
+        Self::new(RECENT_ENTRIES)
+    }
+}
+
+impl<T> Recent<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            pushed: 0,
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// Push an entry, forgetting the oldest one once full.
+    pub fn push(&mut self, value: T) {
+        self.pushed += 1;
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        if self.capacity > 0 {
+            self.entries.push_back(value);
+        }
+    }
+
+    /// The entries kept, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.entries.iter()
+    }
+
+    /// Number of entries ever pushed.
+    pub fn total(&self) -> u64 {
+        self.pushed
+    }
+
+    /// Number of entries pushed but no longer kept.
+    pub fn forgotten(&self) -> u64 {
+        self.pushed - self.entries.len() as u64
+    }
+}
+
+/// The `Timestamp.set` values seen within the window, for duplicate detection.
+pub type TimestampWindow = FirstSeenWindow<u64>;
+
+/// The block that first contained each key, for the keys seen within the window.
+pub struct FirstSeenWindow<K> {
+    blocks: HashMap<K, u32>,
+    window: Window<K>,
+}
+
+impl<K: Hash + Eq + Clone> FirstSeenWindow<K> {
+    pub fn new(config: WindowConfig) -> Self {
+        Self {
+            blocks: HashMap::new(),
+            window: Window::new(config),
+        }
+    }
+
+    /// Insert the key of the given block.
+    ///
+    /// Returns the block that first contained the same key within the window.
+    pub fn insert(&mut self, number: u32, key: K) -> Option<u32> {
+        let blocks = &mut self.blocks;
+        self.window.prune(number, |number, key| {
+            if blocks.get(&key) == Some(&number) {
+                blocks.remove(&key);
+            }
+        });
+
+        match self.blocks.entry(key) {
+            std::collections::hash_map::Entry::Occupied(entry) => Some(*entry.get()),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                self.window.push(number, entry.key().clone());
+                entry.insert(number);
+                None
+            }
+        }
+    }
+
+    /// The block that first contained the key within the window.
+    pub fn get(&self, key: &K) -> Option<u32> {
+        self.blocks.get(key).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocks(blocks: u32) -> WindowConfig {
+        WindowConfig {
+            blocks: Some(blocks),
+            seconds: None,
+        }
+    }
+
+    #[test]
+    fn window_prunes_old_blocks() {
+        let mut window = Window::new(blocks(3));
+        for number in 1..=5 {
+            window.push(number, number * 10);
+        }
+
+        let mut evicted = Vec::new();
+        window.prune(5, |number, value| evicted.push((number, value)));
+
+        assert_eq!(evicted, [(1, 10), (2, 20)]);
+        assert_eq!(window.values().copied().collect::<Vec<_>>(), [30, 40, 50]);
+    }
+
+    #[test]
+    fn window_prunes_old_entries() {
+        let mut window = Window::new(WindowConfig {
+            blocks: None,
+            seconds: Some(0),
+        });
+        window.push(1, ());
+        std::thread::sleep(Duration::from_millis(5));
+
+        window.prune(1, |_, _| {});
+        assert_eq!(window.len(), 0);
+    }
+
+    #[test]
+    fn first_seen_window_reports_the_first_block() {
+        let mut timestamps = FirstSeenWindow::new(blocks(10));
+
+        assert_eq!(timestamps.insert(1, 6_000), None);
+        assert_eq!(timestamps.insert(2, 12_000), None);
+        assert_eq!(timestamps.insert(3, 6_000), Some(1));
+        assert_eq!(timestamps.get(&12_000), Some(2));
+    }
+
+    #[test]
+    fn first_seen_window_forgets_keys_out_of_the_window() {
+        let mut timestamps = FirstSeenWindow::new(blocks(10));
+
+        assert_eq!(timestamps.insert(1, 6_000), None);
+        assert_eq!(timestamps.insert(11, 6_000), None);
+        assert_eq!(timestamps.get(&6_000), Some(11));
+    }
+
+    #[test]
+    fn recent_keeps_the_last_entries() {
+        let mut recent = Recent::new(2);
+        for value in 1..=5 {
+            recent.push(value);
+        }
+
+        assert_eq!(recent.iter().copied().collect::<Vec<_>>(), [4, 5]);
+        assert_eq!(recent.total(), 5);
+        assert_eq!(recent.forgotten(), 3);
+    }
+}